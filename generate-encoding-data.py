@@ -10,6 +10,7 @@
 # except according to those terms.
 
 import json
+import re
 import subprocess
 import sys
 import os.path
@@ -139,6 +140,21 @@ def to_snake_name(name):
 def to_dom_name(name):
   return name
 
+def to_id_name(name):
+  id_name = ""
+  previous_was_number = False
+  for part in name.replace("_", "-").split("-"):
+    for run in re.findall("[A-Za-z]+|[0-9]+", part):
+      if run.isdigit():
+        if previous_was_number:
+          id_name += "_"
+        id_name += run
+        previous_was_number = True
+      else:
+        id_name += run.title()
+        previous_was_number = False
+  return id_name
+
 # Guestimate based on
 # https://w3techs.com/technologies/overview/character_encoding/all
 # whose methodology is known to be bogus, but the results are credible for
@@ -250,6 +266,333 @@ encoding_by_alias_code_page = {
   52936: "replacement", # HZ
 }
 
+# Encodings whose closest Windows code page maps unassigned bytes to the
+# Private Use Area instead of U+FFFD.
+windows_code_page_pua = [
+  "windows-874",
+  "windows-1253",
+  "windows-1255",
+  "windows-1257",
+  "ISO-8859-6",
+  "ISO-8859-7",
+  "ISO-8859-8",
+  "ISO-8859-13",
+]
+
+# Encodings whose closest Windows code page decodes some assigned bytes
+# differently. (The numbered remarks in the crate-level documentation.)
+windows_code_page_differences = [
+  "macintosh",
+  "x-mac-cyrillic",
+  "ISO-8859-7",
+  "ISO-8859-8",
+  "ISO-8859-8-I",
+]
+
+# The IANA MIBenum and preferred MIME name (or the name if there is no
+# preferred MIME name) of the most precise IANA registration for each
+# encoding. See "Notable Differences from IANA Naming" in lib.rs.
+iana_by_encoding = {
+  "Big5": (2101, "Big5-HKSCS"),
+  "EUC-JP": (18, "EUC-JP"),
+  "EUC-KR": (38, "EUC-KR"),
+  "GBK": (113, "GBK"),
+  "IBM866": (2086, "IBM866"),
+  "ISO-2022-JP": (39, "ISO-2022-JP"),
+  "ISO-8859-2": (5, "ISO-8859-2"),
+  "ISO-8859-3": (6, "ISO-8859-3"),
+  "ISO-8859-4": (7, "ISO-8859-4"),
+  "ISO-8859-5": (8, "ISO-8859-5"),
+  "ISO-8859-6": (9, "ISO-8859-6"),
+  "ISO-8859-7": (10, "ISO-8859-7"),
+  "ISO-8859-8": (11, "ISO-8859-8"),
+  "ISO-8859-8-I": (85, "ISO-8859-8-I"),
+  "ISO-8859-10": (13, "ISO-8859-10"),
+  "ISO-8859-13": (109, "ISO-8859-13"),
+  "ISO-8859-14": (110, "ISO-8859-14"),
+  "ISO-8859-15": (111, "ISO-8859-15"),
+  "ISO-8859-16": (112, "ISO-8859-16"),
+  "KOI8-R": (2084, "KOI8-R"),
+  "KOI8-U": (2088, "KOI8-U"),
+  "Shift_JIS": (2024, "Windows-31J"),
+  "UTF-16BE": (1013, "UTF-16BE"),
+  "UTF-16LE": (1014, "UTF-16LE"),
+  "UTF-8": (106, "UTF-8"),
+  "gb18030": (114, "GB18030"),
+  "macintosh": (2027, "macintosh"),
+  "windows-1250": (2250, "windows-1250"),
+  "windows-1251": (2251, "windows-1251"),
+  "windows-1252": (2252, "windows-1252"),
+  "windows-1253": (2253, "windows-1253"),
+  "windows-1254": (2254, "windows-1254"),
+  "windows-1255": (2255, "windows-1255"),
+  "windows-1256": (2256, "windows-1256"),
+  "windows-1257": (2257, "windows-1257"),
+  "windows-1258": (2258, "windows-1258"),
+  "windows-874": (2109, "windows-874"),
+}
+
+encodings_by_iana_mib = {}
+
+for encoding, (mib, iana_name) in iana_by_encoding.items():
+  encodings_by_iana_mib[mib] = encoding
+
+# IANA registrations that the Encoding Standard unifies into an encoding
+# registered under another MIBenum. Each of these agrees with for_label()
+# on the IANA name.
+encodings_by_iana_mib.update({
+  3: "windows-1252", # US-ASCII
+  4: "windows-1252", # ISO-8859-1
+  12: "windows-1254", # ISO-8859-9
+  17: "Shift_JIS", # Shift_JIS
+  36: "EUC-KR", # KS_C_5601-1987
+  37: "replacement", # ISO-2022-KR
+  81: "ISO-8859-6", # ISO-8859-6-E
+  82: "ISO-8859-6", # ISO-8859-6-I
+  84: "ISO-8859-8", # ISO-8859-8-E
+  104: "replacement", # ISO-2022-CN
+  105: "replacement", # ISO-2022-CN-EXT
+  1000: "UTF-16LE", # ISO-10646-UCS-2
+  1015: "UTF-16LE", # UTF-16
+  2025: "GBK", # GB2312
+  2026: "Big5", # Big5
+  2085: "replacement", # HZ-GB-2312
+  2259: "windows-874", # TIS-620
+})
+
+# Names used for character encodings outside the Encoding Standard mapped to
+# the closest encoding and whether the match is exact. These are looked up by
+# for_label_extended() after ASCII-lowercasing and removing everything that
+# isn't ASCII alphanumeric, so spelling variants need not be listed. Names
+# that for_label() already recognizes after such normalization must not be
+# listed.
+#
+# A match is exact if the name is a spelling variant of an Encoding Standard
+# label (the Encoding Standard defines what the label means) or if the
+# encoding decodes every byte sequence that is valid in the named encoding to
+# the same characters. A match with replacement is never exact. The first
+# part is checked below.
+extended_labels = [
+  # Java
+  ("x-windows-949", "EUC-KR", True),
+  ("MS949", "EUC-KR", True),
+  ("x-windows-950", "Big5", False),
+  ("MS950", "Big5", False),
+  ("MS950_HKSCS", "Big5", False),
+  ("MS936", "GBK", True),
+  ("x-mswin-936", "GBK", True),
+  ("x-windows-874", "windows-874", True),
+  ("MS874", "windows-874", True),
+  ("x-eucJP-Open", "EUC-JP", False),
+  ("x-MacRoman", "macintosh", True),
+  ("x-MacCyrillic", "x-mac-cyrillic", True),
+  ("x-MacUkraine", "x-mac-cyrillic", True),
+  ("UnicodeLittleUnmarked", "UTF-16LE", True),
+  ("UnicodeBigUnmarked", "UTF-16BE", True),
+  ("x-UTF-16LE-BOM", "UTF-16LE", True),
+  ("Big5_HKSCS", "Big5", True),
+  ("Big5_Solaris", "Big5", False),
+  # Python codecs
+  ("cp932", "Shift_JIS", True),
+  ("mskanji", "Shift_JIS", True),
+  ("shift_jis", "Shift_JIS", True),
+  ("shift_jis_2004", "Shift_JIS", False),
+  ("shift_jisx0213", "Shift_JIS", False),
+  ("sjis_2004", "Shift_JIS", False),
+  ("sjisx0213", "Shift_JIS", False),
+  ("euc_jp", "EUC-JP", True),
+  ("ujis", "EUC-JP", False), # JIS X 0208 mappings: 0xA1C1 is U+301C
+  ("euc_jis_2004", "EUC-JP", False),
+  ("euc_jisx0213", "EUC-JP", False),
+  ("iso2022_jp", "ISO-2022-JP", True),
+  ("iso2022_jp_1", "ISO-2022-JP", False),
+  ("iso2022_jp_2", "ISO-2022-JP", False),
+  ("iso2022_jp_2004", "ISO-2022-JP", False),
+  ("iso2022_jp_3", "ISO-2022-JP", False),
+  ("iso2022_jp_ext", "ISO-2022-JP", False),
+  ("iso2022_kr", "replacement", False),
+  ("iso2022_cn", "replacement", False),
+  ("iso2022_cn_ext", "replacement", False),
+  ("hz", "replacement", False),
+  ("hzgb", "replacement", False),
+  ("cp949", "EUC-KR", True),
+  ("uhc", "EUC-KR", True),
+  ("euc_kr", "EUC-KR", True),
+  ("cp950", "Big5", False),
+  ("cp936", "GBK", True),
+  ("euc_cn", "GBK", True),
+  ("cp874", "windows-874", True),
+  ("tis620", "windows-874", True),
+  ("koi8_u", "KOI8-U", True),
+  ("mac_roman", "macintosh", True),
+  ("mac_cyrillic", "x-mac-cyrillic", True),
+  ("latin7", "ISO-8859-13", True),
+  ("l7", "ISO-8859-13", True),
+  ("latin8", "ISO-8859-14", True),
+  ("l8", "ISO-8859-14", True),
+  ("latin9", "ISO-8859-15", True),
+  ("latin10", "ISO-8859-16", True),
+  ("l10", "ISO-8859-16", True),
+  ("utf_16_le", "UTF-16LE", True),
+  ("utf_16_be", "UTF-16BE", True),
+  ("utf_8_sig", "UTF-8", True),
+  ("u8", "UTF-8", True),
+  ("cp65001", "UTF-8", True),
+  ("utf_16", "UTF-16LE", True),
+  # ICU and iconv
+  ("eucJP-ms", "EUC-JP", False),
+  ("eucJP-open", "EUC-JP", False),
+  ("windows-936-2000", "GBK", True),
+  ("UCS-2LE", "UTF-16LE", True),
+  ("UCS-2BE", "UTF-16BE", True),
+  # Databases
+  ("utf8mb4", "UTF-8", True),
+  ("utf8mb3", "UTF-8", True),
+  ("WIN1250", "windows-1250", True),
+  ("WIN1251", "windows-1251", True),
+  ("WIN1252", "windows-1252", True),
+  ("WIN1253", "windows-1253", True),
+  ("WIN1254", "windows-1254", True),
+  ("WIN1255", "windows-1255", True),
+  ("WIN1256", "windows-1256", True),
+  ("WIN1257", "windows-1257", True),
+  ("WIN1258", "windows-1258", True),
+  ("WIN866", "IBM866", True),
+  ("WIN874", "windows-874", True),
+  ("AL32UTF8", "UTF-8", True),
+  ("AL16UTF16", "UTF-16BE", True),
+  ("JA16SJIS", "Shift_JIS", False),
+  ("JA16SJISTILDE", "Shift_JIS", False),
+  ("JA16EUC", "EUC-JP", False),
+  ("JA16EUCTILDE", "EUC-JP", False),
+  ("ZHS16GBK", "GBK", True),
+  ("ZHS32GB18030", "gb18030", True),
+  ("ZHT16BIG5", "Big5", False),
+  ("ZHT16HKSCS", "Big5", False),
+  ("ZHT16MSWIN950", "Big5", False),
+  ("KO16MSWIN949", "EUC-KR", True),
+  ("KO16KSC5601", "EUC-KR", True),
+  ("US7ASCII", "windows-1252", True),
+  ("WE8ISO8859P1", "windows-1252", False),
+  ("WE8MSWIN1252", "windows-1252", True),
+  ("EE8MSWIN1250", "windows-1250", True),
+  ("CL8MSWIN1251", "windows-1251", True),
+  ("EL8MSWIN1253", "windows-1253", True),
+  ("TR8MSWIN1254", "windows-1254", True),
+  ("IW8MSWIN1255", "windows-1255", True),
+  ("AR8MSWIN1256", "windows-1256", True),
+  ("BLT8MSWIN1257", "windows-1257", True),
+  ("VN8MSWIN1258", "windows-1258", True),
+  ("TH8TISASCII", "windows-874", True),
+  ("CL8KOI8R", "KOI8-R", True),
+  ("CL8KOI8U", "KOI8-U", False), # RFC 2319 mappings: 0xAE is U+255D
+  ("EE8ISO8859P2", "ISO-8859-2", True),
+  ("WE8ISO8859P15", "ISO-8859-15", True),
+]
+
+def normalize_extended_label(label):
+  return "".join(c for c in label.lower() if c.isascii() and c.isalnum())
+
+extended_labels_by_normalized = {}
+
+for (label, encoding, exact) in extended_labels:
+  normalized = normalize_extended_label(label)
+  if normalized in extended_labels_by_normalized:
+    sys.stderr.write("Duplicate extended label %s. Exiting.\n" % label)
+    sys.exit(-1)
+  extended_labels_by_normalized[normalized] = (encoding, exact)
+
+# The English title and script group of each encoding for encoding menus.
+# The titles follow the titles in Firefox.
+display_titles_and_script_groups = {
+  "Big5": ("Chinese, Traditional (Big5)", "ChineseTraditional"),
+  "EUC-JP": ("Japanese (EUC-JP)", "Japanese"),
+  "EUC-KR": ("Korean (EUC-KR)", "Korean"),
+  "GBK": ("Chinese, Simplified (GBK)", "ChineseSimplified"),
+  "IBM866": ("Cyrillic (IBM-866)", "Cyrillic"),
+  "ISO-2022-JP": ("Japanese (ISO-2022-JP)", "Japanese"),
+  "ISO-8859-10": ("Nordic (ISO-8859-10)", "Western"),
+  "ISO-8859-13": ("Baltic (ISO-8859-13)", "Baltic"),
+  "ISO-8859-14": ("Celtic (ISO-8859-14)", "Western"),
+  "ISO-8859-15": ("Western (ISO-8859-15)", "Western"),
+  "ISO-8859-16": ("Romanian (ISO-8859-16)", "CentralEuropean"),
+  "ISO-8859-2": ("Central European (ISO-8859-2)", "CentralEuropean"),
+  "ISO-8859-3": ("South European (ISO-8859-3)", "Western"),
+  "ISO-8859-4": ("Baltic (ISO-8859-4)", "Baltic"),
+  "ISO-8859-5": ("Cyrillic (ISO-8859-5)", "Cyrillic"),
+  "ISO-8859-6": ("Arabic (ISO-8859-6)", "Arabic"),
+  "ISO-8859-7": ("Greek (ISO-8859-7)", "Greek"),
+  "ISO-8859-8": ("Hebrew, Visual (ISO-8859-8)", "Hebrew"),
+  "ISO-8859-8-I": ("Hebrew (ISO-8859-8-I)", "Hebrew"),
+  "KOI8-R": ("Cyrillic (KOI8-R)", "Cyrillic"),
+  "KOI8-U": ("Cyrillic/Ukrainian (KOI8-U)", "Cyrillic"),
+  "Shift_JIS": ("Japanese (Shift_JIS)", "Japanese"),
+  "UTF-16BE": ("Unicode (UTF-16BE)", "Unicode"),
+  "UTF-16LE": ("Unicode (UTF-16LE)", "Unicode"),
+  "UTF-8": ("Unicode (UTF-8)", "Unicode"),
+  "gb18030": ("Chinese, Simplified (gb18030)", "ChineseSimplified"),
+  "macintosh": ("Western (MacRoman)", "Western"),
+  "replacement": ("Replacement", "Other"),
+  "windows-1250": ("Central European (Windows-1250)", "CentralEuropean"),
+  "windows-1251": ("Cyrillic (Windows-1251)", "Cyrillic"),
+  "windows-1252": ("Western (Windows-1252)", "Western"),
+  "windows-1253": ("Greek (Windows-1253)", "Greek"),
+  "windows-1254": ("Turkish (Windows-1254)", "Turkish"),
+  "windows-1255": ("Hebrew (Windows-1255)", "Hebrew"),
+  "windows-1256": ("Arabic (Windows-1256)", "Arabic"),
+  "windows-1257": ("Baltic (Windows-1257)", "Baltic"),
+  "windows-1258": ("Vietnamese (Windows-1258)", "Vietnamese"),
+  "windows-874": ("Thai (Windows-874)", "Thai"),
+  "x-mac-cyrillic": ("Cyrillic (MacCyrillic)", "Cyrillic"),
+  "x-user-defined": ("User Defined (x-user-defined)", "Other"),
+}
+
+# The u8 discriminants of EncodingId, which are promised to be stable.
+# Existing entries must never change. A new encoding gets the next unused
+# number. test_encoding_id_values in src/lib.rs pins the existing entries.
+encoding_ids = {
+  "Big5": 0,
+  "EUC-JP": 1,
+  "EUC-KR": 2,
+  "GBK": 3,
+  "IBM866": 4,
+  "ISO-2022-JP": 5,
+  "ISO-8859-10": 6,
+  "ISO-8859-13": 7,
+  "ISO-8859-14": 8,
+  "ISO-8859-15": 9,
+  "ISO-8859-16": 10,
+  "ISO-8859-2": 11,
+  "ISO-8859-3": 12,
+  "ISO-8859-4": 13,
+  "ISO-8859-5": 14,
+  "ISO-8859-6": 15,
+  "ISO-8859-7": 16,
+  "ISO-8859-8": 17,
+  "ISO-8859-8-I": 18,
+  "KOI8-R": 19,
+  "KOI8-U": 20,
+  "Shift_JIS": 21,
+  "UTF-16BE": 22,
+  "UTF-16LE": 23,
+  "UTF-8": 24,
+  "gb18030": 25,
+  "macintosh": 26,
+  "replacement": 27,
+  "windows-1250": 28,
+  "windows-1251": 29,
+  "windows-1252": 30,
+  "windows-1253": 31,
+  "windows-1254": 32,
+  "windows-1255": 33,
+  "windows-1256": 34,
+  "windows-1257": 35,
+  "windows-1258": 36,
+  "windows-874": 37,
+  "x-mac-cyrillic": 38,
+  "x-user-defined": 39,
+}
+
 code_pages = []
 
 for name in encodings_by_code_page_frequency:
@@ -318,10 +661,34 @@ for group in data:
 for name in preferred:
   dom.append(to_dom_name(name))
 
+for label in labels:
+  extended = extended_labels_by_normalized.get(normalize_extended_label(label.label))
+  if extended and extended != (label.preferred, label.preferred != "replacement"):
+    sys.stderr.write("Extended label inconsistent with %s. Exiting.\n" % label.label)
+    sys.exit(-1)
+
 preferred.sort()
 labels.sort()
+
+if sorted(encoding_ids.keys()) != preferred:
+  sys.stderr.write("encoding_ids doesn't cover exactly the encodings. Exiting.\n")
+  sys.exit(-1)
+
+if sorted(encoding_ids.values()) != list(range(len(preferred))):
+  sys.stderr.write("encoding_ids must be consecutive starting from 0. Exiting.\n")
+  sys.exit(-1)
+
+preferred_in_id_order = sorted(preferred, key=lambda name: encoding_ids[name])
 dom.sort(key=cmp_to_key(cmp_from_end))
 
+longest_extended_label_length = 0
+longest_extended_label = None
+
+for label in extended_labels_by_normalized.keys():
+  if len(label) > longest_extended_label_length:
+    longest_extended_label_length = len(label)
+    longest_extended_label = label
+
 longest_label_length = 0
 longest_name_length = 0
 longest_label = None
@@ -392,7 +759,9 @@ label_file.write("""
 
 const LONGEST_LABEL_LENGTH: usize = %d; // %s
 
-""" % (longest_label_length, longest_label))
+const LONGEST_EXTENDED_LABEL_LENGTH: usize = %d; // %s
+
+""" % (longest_label_length, longest_label, longest_extended_label_length, longest_extended_label))
 
 for name in preferred:
   variant = None
@@ -421,6 +790,7 @@ for name in preferred:
 pub static %s_INIT: Encoding = Encoding {
     name: "%s",
     variant: VariantEncoding::%s,
+    id: EncodingId::%s,
 };
 
 /// The %s encoding.
@@ -432,7 +802,7 @@ pub static %s_INIT: Encoding = Encoding {
 /// `static`.
 pub static %s: &'static Encoding = &%s_INIT;
 
-''' % (to_dom_name(name), to_constant_name(name), to_constant_name(name), to_dom_name(name), variant, to_dom_name(name), doctext, to_constant_name(name), to_constant_name(name)))
+''' % (to_dom_name(name), to_constant_name(name), to_constant_name(name), to_dom_name(name), variant, to_id_name(name), to_dom_name(name), doctext, to_constant_name(name), to_constant_name(name)))
 
 label_file.write("""static LABELS_SORTED: [&'static str; %d] = [
 """ % len(labels))
@@ -450,6 +820,177 @@ for label in labels:
 
 label_file.write('''];
 
+static ENCODINGS_IN_NAME_SORT: [&'static Encoding; %d] = [
+''' % len(preferred))
+
+for name in preferred:
+  label_file.write('''&%s_INIT,\n''' % to_constant_name(name))
+
+label_file.write('''];
+
+static ENCODINGS_IN_ID_ORDER: [&'static Encoding; %d] = [
+''' % len(preferred))
+
+for name in preferred_in_id_order:
+  label_file.write('''&%s_INIT,\n''' % to_constant_name(name))
+
+label_file.write('''];
+
+static NAME_SORT_INDICES_IN_ID_ORDER: [u8; %d] = [
+''' % len(preferred))
+
+for name in preferred_in_id_order:
+  label_file.write('''%d,\n''' % preferred.index(name))
+
+label_file.write('''];
+
+/// Identifies an encoding as a value that can be used with `match`.
+///
+/// There is one variant per encoding. The `u8` discriminants are stable, so
+/// `EncodingId` can be stored in a single byte using `u8::from()` and
+/// restored using `EncodingId::try_from()`. The discriminants are not the
+/// positions in `Encoding::all()`, since a new encoding gets the next unused
+/// discriminant.
+///
+/// Obtained from `Encoding::id()`. The encoding can be obtained back using
+/// `EncodingId::encoding()`.
+///
+/// When serialized using Serde (with the `serde` feature enabled),
+/// `EncodingId` is represented as the name of the encoding in
+/// human-readable formats and as the `u8` discriminant in other formats.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum EncodingId {
+''')
+
+for name in preferred_in_id_order:
+  label_file.write('''/// The [%s](static.%s.html) encoding.
+%s = %d,
+''' % (to_dom_name(name), to_constant_name(name), to_id_name(name), encoding_ids[name]))
+
+label_file.write('''}
+
+static DISPLAY_TITLES_IN_NAME_SORT: [&'static str; %d] = [
+''' % len(preferred))
+
+for name in preferred:
+  label_file.write('''"%s",\n''' % display_titles_and_script_groups[name][0])
+
+label_file.write('''];
+
+static SCRIPT_GROUPS_IN_NAME_SORT: [ScriptGroup; %d] = [
+''' % len(preferred))
+
+for name in preferred:
+  label_file.write('''ScriptGroup::%s,\n''' % display_titles_and_script_groups[name][1])
+
+label_file.write('''];
+
+static WINDOWS_CODE_PAGES_IN_NAME_SORT: [u16; %d] = [
+''' % len(preferred))
+
+for name in preferred:
+  label_file.write('''%d,\n''' % code_pages_by_encoding.get(name, 0))
+
+label_file.write('''];
+
+static WINDOWS_CODE_PAGE_PUA_IN_NAME_SORT: [bool; %d] = [
+''' % len(preferred))
+
+for name in preferred:
+  label_file.write('''%s,\n''' % ("true" if name in windows_code_page_pua else "false"))
+
+label_file.write('''];
+
+static WINDOWS_CODE_PAGE_DIFFERENCES_IN_NAME_SORT: [bool; %d] = [
+''' % len(preferred))
+
+for name in preferred:
+  label_file.write('''%s,\n''' % ("true" if name in windows_code_page_differences else "false"))
+
+sorted_code_pages = list(encodings_by_code_page.keys())
+sorted_code_pages.sort()
+
+label_file.write('''];
+
+static WINDOWS_CODE_PAGES_SORTED: [u16; %d] = [
+''' % len(sorted_code_pages))
+
+for code_page in sorted_code_pages:
+  label_file.write('''%d,\n''' % code_page)
+
+label_file.write('''];
+
+static ENCODINGS_IN_WINDOWS_CODE_PAGE_SORT: [&'static Encoding; %d] = [
+''' % len(sorted_code_pages))
+
+for code_page in sorted_code_pages:
+  label_file.write('''&%s_INIT,\n''' % to_constant_name(encodings_by_code_page[code_page]))
+
+label_file.write('''];
+
+static IANA_MIBS_IN_NAME_SORT: [u16; %d] = [
+''' % len(preferred))
+
+for name in preferred:
+  label_file.write('''%d,\n''' % iana_by_encoding.get(name, (0, ""))[0])
+
+label_file.write('''];
+
+static IANA_NAMES_IN_NAME_SORT: [&'static str; %d] = [
+''' % len(preferred))
+
+for name in preferred:
+  label_file.write('''"%s",\n''' % iana_by_encoding.get(name, (0, ""))[1])
+
+sorted_iana_mibs = list(encodings_by_iana_mib.keys())
+sorted_iana_mibs.sort()
+
+label_file.write('''];
+
+static IANA_MIBS_SORTED: [u16; %d] = [
+''' % len(sorted_iana_mibs))
+
+for mib in sorted_iana_mibs:
+  label_file.write('''%d,\n''' % mib)
+
+label_file.write('''];
+
+static ENCODINGS_IN_IANA_MIB_SORT: [&'static Encoding; %d] = [
+''' % len(sorted_iana_mibs))
+
+for mib in sorted_iana_mibs:
+  label_file.write('''&%s_INIT,\n''' % to_constant_name(encodings_by_iana_mib[mib]))
+
+sorted_extended_labels = list(extended_labels_by_normalized.keys())
+sorted_extended_labels.sort()
+
+label_file.write('''];
+
+static EXTENDED_LABELS_SORTED: [&'static str; %d] = [
+''' % len(sorted_extended_labels))
+
+for label in sorted_extended_labels:
+  label_file.write('''"%s",\n''' % label)
+
+label_file.write('''];
+
+static ENCODINGS_IN_EXTENDED_LABEL_SORT: [&'static Encoding; %d] = [
+''' % len(sorted_extended_labels))
+
+for label in sorted_extended_labels:
+  label_file.write('''&%s_INIT,\n''' % to_constant_name(extended_labels_by_normalized[label][0]))
+
+label_file.write('''];
+
+static EXTENDED_LABELS_EXACT: [bool; %d] = [
+''' % len(sorted_extended_labels))
+
+for label in sorted_extended_labels:
+  label_file.write('''%s,\n''' % ("true" if extended_labels_by_normalized[label][1] else "false"))
+
+label_file.write('''];
+
 ''')
 label_file.write(lib_rs_end)
 label_file.close()
@@ -1377,6 +1918,8 @@ write_variant_method("max_buffer_length_from_utf16_without_replacement", False,
 
 write_variant_method("max_buffer_length_from_utf8_without_replacement", False, False, [("byte_length", "usize")], "Option<usize>", encoder_variants, [], "Encoder")
 
+write_variant_method("can_encode", False, False, [("c", "char")], "bool", encoder_variants, [], "Encoder")
+
 write_variant_method("encode_from_utf16_raw", False, True, [("src", "&[u16]"),
                            ("dst", "&mut [u8]"),
                            ("last", "bool")], "(EncoderResult, usize, usize)", encoder_variants, [], "Encoder")
@@ -0,0 +1,474 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adapters between the `futures-io` traits and the streaming API.
+
+use super::*;
+use crate::io::{DecodeState, EncodeState, IO_BUFFER_LENGTH};
+use core::pin::Pin;
+use core::task::{Context, Poll, ready};
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+use std::io;
+
+/// Wraps a `futures_io::AsyncRead` and presents its bytes decoded into
+/// UTF-8 via `futures_io::AsyncRead` and `futures_io::AsyncBufRead`.
+///
+/// This is the asynchronous counterpart of `DecodeReader` and behaves the
+/// same way. At most 8 KiB of input and 8 KiB of output are buffered.
+///
+/// With Tokio, the `compat` module of the `tokio-util` crate converts
+/// between these traits and Tokio's.
+pub struct AsyncDecodeReader<R> {
+    inner: R,
+    state: DecodeState,
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecodeReader<R> {
+    /// Wraps `inner` so that malformed sequences are replaced with the
+    /// REPLACEMENT CHARACTER.
+    pub fn new(inner: R, decoder: Decoder) -> AsyncDecodeReader<R> {
+        AsyncDecodeReader {
+            inner,
+            state: DecodeState::new(decoder, true),
+        }
+    }
+
+    /// Wraps `inner` so that a malformed sequence results in an error of
+    /// the kind `std::io::ErrorKind::InvalidData`.
+    ///
+    /// The output preceding the malformed sequence is returned before the
+    /// error. After the error, reading continues after the malformed
+    /// sequence.
+    pub fn new_without_replacement(inner: R, decoder: Decoder) -> AsyncDecodeReader<R> {
+        AsyncDecodeReader {
+            inner,
+            state: DecodeState::new(decoder, false),
+        }
+    }
+
+    /// The encoding of the decoder. BOM sniffing can change the return
+    /// value during reading.
+    #[inline]
+    pub fn encoding(&self) -> &'static Encoding {
+        self.state.decoder().encoding()
+    }
+
+    /// Returns a reference to the wrapped reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped reader.
+    ///
+    /// Reading from the wrapped reader directly desynchronizes this reader.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped reader. Buffered data is lost.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncBufRead for AsyncDecodeReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        loop {
+            this.state.fill()?;
+            if !this.state.wants_input() {
+                return Poll::Ready(Ok(this.state.output()));
+            }
+            let read = ready!(Pin::new(&mut this.inner).poll_read(cx, this.state.input_buffer()))?;
+            this.state.set_input(read);
+        }
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().state.consume(amt);
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncDecodeReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let available = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let len = core::cmp::min(available.len(), buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl<R> core::fmt::Debug for AsyncDecodeReader<R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("AsyncDecodeReader")
+            .field("decoder", self.state.decoder())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Wraps a `futures_io::AsyncWrite` and encodes the UTF-8 written to it.
+///
+/// This is the asynchronous counterpart of `EncodeWriter` and behaves the
+/// same way except that the end of the stream is signaled by closing the
+/// writer: `poll_close` writes the remaining output (the escape sequence
+/// back to ASCII for ISO-2022-JP) before closing the wrapped writer. At
+/// most 8 KiB of output is buffered.
+///
+/// With Tokio, the `compat` module of the `tokio-util` crate converts
+/// between these traits and Tokio's.
+pub struct AsyncEncodeWriter<W> {
+    inner: W,
+    state: EncodeState,
+    output: Vec<u8>,
+    output_start: usize,
+    output_end: usize,
+    finished: bool,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncEncodeWriter<W> {
+    /// Wraps `inner` so that unmappable characters are replaced with
+    /// HTML decimal numeric character references.
+    pub fn new(inner: W, encoder: Encoder) -> AsyncEncodeWriter<W> {
+        AsyncEncodeWriter::with_state(inner, EncodeState::new(encoder, true))
+    }
+
+    /// Wraps `inner` so that an unmappable character results in an error of
+    /// the kind `std::io::ErrorKind::InvalidData` whose payload is an
+    /// `UnmappableError`.
+    pub fn new_without_replacement(inner: W, encoder: Encoder) -> AsyncEncodeWriter<W> {
+        AsyncEncodeWriter::with_state(inner, EncodeState::new(encoder, false))
+    }
+
+    fn with_state(inner: W, state: EncodeState) -> AsyncEncodeWriter<W> {
+        AsyncEncodeWriter {
+            inner,
+            state,
+            output: vec![0; IO_BUFFER_LENGTH],
+            output_start: 0,
+            output_end: 0,
+            finished: false,
+        }
+    }
+
+    /// The encoding of the encoder.
+    #[inline]
+    pub fn encoding(&self) -> &'static Encoding {
+        self.state.encoder().encoding()
+    }
+
+    /// Returns a reference to the wrapped writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped writer.
+    ///
+    /// Writing to the wrapped writer directly can break the statefulness
+    /// of ISO-2022-JP.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped writer. Buffered output is lost.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Writes the buffered output to the wrapped writer.
+    fn poll_write_output(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.output_start < self.output_end {
+            let written = ready!(
+                Pin::new(&mut self.inner)
+                    .poll_write(cx, &self.output[self.output_start..self.output_end])
+            )?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.output_start += written;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncEncodeWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(Err(io::Error::other("write after close")));
+        }
+        loop {
+            ready!(this.poll_write_output(cx))?;
+            if buf.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+            let (read, written) = this.state.encode(buf, &mut this.output)?;
+            this.output_start = 0;
+            this.output_end = written;
+            // Retry after output that didn't consume input, since zero
+            // would mean that the writer can't accept more.
+            if read != 0 {
+                return Poll::Ready(Ok(read));
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_output(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_output(cx))?;
+        if !this.finished {
+            let written = this.state.finish(&mut this.output)?;
+            this.finished = true;
+            this.output_start = 0;
+            this.output_end = written;
+            ready!(this.poll_write_output(cx))?;
+        }
+        Pin::new(&mut this.inner).poll_close(cx)
+    }
+}
+
+impl<W> core::fmt::Debug for AsyncEncodeWriter<W> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("AsyncEncodeWriter")
+            .field("encoder", self.state.encoder())
+            .finish_non_exhaustive()
+    }
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use core::pin::Pin;
+    use core::task::{Context, Poll, Waker};
+    use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+    use std::io;
+
+    /// One end of an in-memory pipe that moves at most `chunk` bytes per
+    /// call and returns `Poll::Pending` on every other call.
+    struct Pipe {
+        data: Vec<u8>,
+        chunk: usize,
+        stall: bool,
+        closed: bool,
+    }
+
+    impl Pipe {
+        fn new(data: &[u8], chunk: usize) -> Pipe {
+            Pipe {
+                data: data.to_vec(),
+                chunk,
+                stall: false,
+                closed: false,
+            }
+        }
+
+        fn stalls(&mut self, cx: &mut Context<'_>) -> bool {
+            self.stall = !self.stall;
+            if self.stall {
+                cx.waker().wake_by_ref();
+            }
+            self.stall
+        }
+    }
+
+    impl AsyncRead for Pipe {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            if this.stalls(cx) {
+                return Poll::Pending;
+            }
+            let len = this.chunk.min(buf.len()).min(this.data.len());
+            buf[..len].copy_from_slice(&this.data[..len]);
+            this.data.drain(..len);
+            Poll::Ready(Ok(len))
+        }
+    }
+
+    impl AsyncWrite for Pipe {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            assert!(!this.closed);
+            if this.stalls(cx) {
+                return Poll::Pending;
+            }
+            let len = this.chunk.min(buf.len());
+            this.data.extend_from_slice(&buf[..len]);
+            Poll::Ready(Ok(len))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            self.get_mut().closed = true;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Polls until ready. The pipes wake themselves when stalling.
+    fn block_on<T>(mut poll: impl FnMut(&mut Context<'_>) -> Poll<T>) -> T {
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(t) = poll(&mut cx) {
+                return t;
+            }
+        }
+    }
+
+    fn read_to_end(reader: &mut (impl AsyncRead + Unpin)) -> io::Result<Vec<u8>> {
+        let mut vec = Vec::new();
+        let mut buf = [0u8; 5];
+        loop {
+            let read = block_on(|cx| Pin::new(&mut *reader).poll_read(cx, &mut buf))?;
+            if read == 0 {
+                return Ok(vec);
+            }
+            vec.extend_from_slice(&buf[..read]);
+        }
+    }
+
+    fn write_all(writer: &mut (impl AsyncWrite + Unpin), mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            let written = block_on(|cx| Pin::new(&mut *writer).poll_write(cx, buf))?;
+            buf = &buf[written..];
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_async_decode_reader() {
+        let string = "こんにちは、世界！".repeat(1000);
+        let (bytes, _, _) = SHIFT_JIS.encode(&string);
+        for chunk in [1, 3, 10000] {
+            let mut reader =
+                AsyncDecodeReader::new(Pipe::new(&bytes, chunk), SHIFT_JIS.new_decoder());
+            assert_eq!(read_to_end(&mut reader).unwrap(), string.as_bytes());
+        }
+        // `last` at EOF
+        let mut reader = AsyncDecodeReader::new(Pipe::new(b"a\x82", 1), SHIFT_JIS.new_decoder());
+        assert_eq!(read_to_end(&mut reader).unwrap(), "a\u{FFFD}".as_bytes());
+        // BOM sniffing
+        let mut reader =
+            AsyncDecodeReader::new(Pipe::new(b"\xFE\xFF\x00a", 1), SHIFT_JIS.new_decoder());
+        assert_eq!(read_to_end(&mut reader).unwrap(), b"a");
+        assert_eq!(reader.encoding(), UTF_16BE);
+    }
+
+    #[test]
+    fn test_async_decode_reader_buf_read() {
+        let mut reader =
+            AsyncDecodeReader::new(Pipe::new(b"ab\xE9", 2), WINDOWS_1252.new_decoder());
+        let available = block_on(|cx| {
+            Pin::new(&mut reader)
+                .poll_fill_buf(cx)
+                .map_ok(<[u8]>::to_vec)
+        })
+        .unwrap();
+        assert_eq!(available, b"ab");
+        Pin::new(&mut reader).consume(1);
+        let available = block_on(|cx| {
+            Pin::new(&mut reader)
+                .poll_fill_buf(cx)
+                .map_ok(<[u8]>::to_vec)
+        })
+        .unwrap();
+        assert_eq!(available, b"b");
+        Pin::new(&mut reader).consume(1);
+        assert_eq!(read_to_end(&mut reader).unwrap(), "é".as_bytes());
+    }
+
+    #[test]
+    fn test_async_decode_reader_without_replacement() {
+        let mut reader = AsyncDecodeReader::new_without_replacement(
+            Pipe::new(b"ab\xFFcd", 10),
+            UTF_8.new_decoder(),
+        );
+        let mut buf = [0u8; 10];
+        assert_eq!(
+            block_on(|cx| Pin::new(&mut reader).poll_read(cx, &mut buf)).unwrap(),
+            2
+        );
+        let err = block_on(|cx| Pin::new(&mut reader).poll_read(cx, &mut buf)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(read_to_end(&mut reader).unwrap(), b"cd");
+    }
+
+    #[test]
+    fn test_async_encode_writer() {
+        let string = "日本語".repeat(3000);
+        for chunk in [1, 7, 100000] {
+            let mut writer =
+                AsyncEncodeWriter::new(Pipe::new(b"", chunk), ISO_2022_JP.new_encoder());
+            // Split UTF-8 sequences across writes
+            for piece in string.as_bytes().chunks(4) {
+                write_all(&mut writer, piece).unwrap();
+            }
+            block_on(|cx| Pin::new(&mut writer).poll_close(cx)).unwrap();
+            let pipe = writer.into_inner();
+            assert!(pipe.closed);
+            assert!(pipe.data.ends_with(b"\x1B(B"));
+            let (decoded, malformed) = ISO_2022_JP.decode_without_bom_handling(&pipe.data);
+            assert!(!malformed);
+            assert_eq!(decoded, string);
+        }
+    }
+
+    #[test]
+    fn test_async_encode_writer_errors() {
+        let mut writer =
+            AsyncEncodeWriter::new_without_replacement(Pipe::new(b"", 3), EUC_KR.new_encoder());
+        let buf = "a\u{00E9}".as_bytes();
+        assert_eq!(
+            block_on(|cx| Pin::new(&mut writer).poll_write(cx, buf)).unwrap(),
+            1
+        );
+        let err = block_on(|cx| Pin::new(&mut writer).poll_write(cx, &buf[1..])).unwrap_err();
+        let unmappable = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<UnmappableError>()
+            .unwrap();
+        assert_eq!(unmappable.character(), '\u{00E9}');
+        write_all(&mut writer, b"\xE4").unwrap();
+        let err = block_on(|cx| Pin::new(&mut writer).poll_close(cx)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -192,6 +192,21 @@ impl Big5Encoder {
         byte_length.checked_add(1)
     }
 
+    pub fn can_encode(&self, c: char) -> bool {
+        if c <= '\u{7F}' {
+            return true;
+        }
+        if c > '\u{FFFF}' {
+            let astral = c as u32;
+            return in_inclusive_range32(astral, 0x2008A, 0x2F8A6)
+                && big5_astral_encode(astral as u16).is_some();
+        }
+        let bmp = c as u16;
+        big5_level1_hanzi_encode(bmp).is_some()
+            || big5_box_encode(bmp).is_some()
+            || big5_other_encode(bmp).is_some()
+    }
+
     ascii_compatible_encoder_functions!(
         {
             // For simplicity, unified ideographs
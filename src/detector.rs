@@ -0,0 +1,628 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use crate::data::{
+    big5_level1_hanzi_encode, gb2312_level1_hanzi_encode, gb2312_level2_hanzi_encode,
+};
+
+/// What kind of text a candidate encoding is expected to carry.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    /// Latin with the non-ASCII letters that are common in the languages
+    /// that the encoding is used for.
+    Latin(&'static str),
+    Cyrillic,
+    Greek,
+    Hebrew,
+    Arabic,
+    Thai,
+    Japanese,
+    Korean,
+    Simplified,
+    Traditional,
+}
+
+/// Scripts of the letters that the single-byte encodings carry.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Hebrew,
+    Arabic,
+    Thai,
+}
+
+/// The class of a decoded character for the purpose of scoring pairs of
+/// adjacent characters.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Class {
+    /// ASCII other than letters and anything else that doesn't affect the
+    /// score. Also the state at the start of the stream.
+    Other,
+    AsciiLetter,
+    /// A non-ASCII letter. Caseless scripts use `upper: false`.
+    Letter {
+        script: Script,
+        upper: bool,
+    },
+    /// A combining mark (as used by windows-1258).
+    Combining,
+    Kana,
+    HalfWidthKana,
+    Hangul,
+    Han,
+    /// Non-ASCII punctuation and currency that occurs in ordinary text.
+    Punctuation,
+    /// Other non-ASCII non-letters.
+    Symbol,
+}
+
+/// The candidates in order of preference when scores are equal.
+static CANDIDATES: [(&Encoding, Kind); 20] = [
+    (&WINDOWS_1252_INIT, Kind::Latin(WESTERN)),
+    (&WINDOWS_1250_INIT, Kind::Latin(CENTRAL)),
+    (&ISO_8859_2_INIT, Kind::Latin(CENTRAL)),
+    (&WINDOWS_1254_INIT, Kind::Latin(TURKISH)),
+    (&WINDOWS_1257_INIT, Kind::Latin(BALTIC)),
+    (&WINDOWS_1258_INIT, Kind::Latin(VIETNAMESE)),
+    (&WINDOWS_1251_INIT, Kind::Cyrillic),
+    (&KOI8_U_INIT, Kind::Cyrillic),
+    (&IBM866_INIT, Kind::Cyrillic),
+    (&WINDOWS_1253_INIT, Kind::Greek),
+    (&ISO_8859_7_INIT, Kind::Greek),
+    (&WINDOWS_1255_INIT, Kind::Hebrew),
+    (&WINDOWS_1256_INIT, Kind::Arabic),
+    (&WINDOWS_874_INIT, Kind::Thai),
+    (&SHIFT_JIS_INIT, Kind::Japanese),
+    (&EUC_JP_INIT, Kind::Japanese),
+    (&ISO_2022_JP_INIT, Kind::Japanese),
+    (&EUC_KR_INIT, Kind::Korean),
+    (&GBK_INIT, Kind::Simplified),
+    (&BIG5_INIT, Kind::Traditional),
+];
+
+// The non-ASCII lower-case letters that are common in the languages that
+// each group of Latin encodings is used for.
+
+static WESTERN: &str = "áàâäãåæçéèêëíîïñóòôöõøúùûüýÿßœ";
+
+static CENTRAL: &str = "áäâăçéëíîóöôúüýßčćďěęłńňőřśšșşťțůűźżž";
+
+static TURKISH: &str = "âçéêîöûüğış";
+
+static BALTIC: &str = "äåæéõöøüāąčēėęģīįķļņšūųž";
+
+static VIETNAMESE: &str = "àáâãèéêìíòóôõùúýăđơư";
+
+/// The Cyrillic letters that are the most common in Russian, Ukrainian and
+/// Bulgarian text. Other Cyrillic letters score lower.
+static COMMON_CYRILLIC: &str = "оеаинтсрвлкмдпуяызьбгчіїє";
+
+/// The most common Greek letters.
+static COMMON_GREEK: &str = "αεηιοτνσςκπρμυλάέίόήύώ";
+
+/// The most common Arabic letters.
+static COMMON_ARABIC: &str = "اأإلميونهربتةعدفقسكح";
+
+fn script_for_kind(kind: Kind) -> Option<Script> {
+    match kind {
+        Kind::Latin(_) => Some(Script::Latin),
+        Kind::Cyrillic => Some(Script::Cyrillic),
+        Kind::Greek => Some(Script::Greek),
+        Kind::Hebrew => Some(Script::Hebrew),
+        Kind::Arabic => Some(Script::Arabic),
+        Kind::Thai => Some(Script::Thai),
+        _ => None,
+    }
+}
+
+/// Classifies a decoded character. Returns `None` for characters whose
+/// presence rules out the candidate (C1 controls and the Private Use Area).
+fn classify(c: char) -> Option<Class> {
+    let scalar = c as u32;
+    if scalar < 0x80 {
+        return Some(if c.is_ascii_alphabetic() {
+            Class::AsciiLetter
+        } else {
+            Class::Other
+        });
+    }
+    if scalar < 0xA0 || (0xE000..=0xF8FF).contains(&scalar) {
+        return None;
+    }
+    let script = match scalar {
+        0x00C0..=0x024F | 0x1E00..=0x1EFF => Some(Script::Latin),
+        0x0370..=0x03FF => Some(Script::Greek),
+        0x0400..=0x052F => Some(Script::Cyrillic),
+        0x0590..=0x05FF => Some(Script::Hebrew),
+        0x0600..=0x06FF => Some(Script::Arabic),
+        0x0E00..=0x0E7F => Some(Script::Thai),
+        _ => None,
+    };
+    if let Some(script) = script {
+        if c.is_alphabetic() {
+            return Some(Class::Letter {
+                script,
+                upper: c.is_uppercase(),
+            });
+        }
+        if script == Script::Latin {
+            // × and ÷
+            return Some(Class::Symbol);
+        }
+        // Hebrew points, Arabic harakat and Thai vowel signs and marks
+        return Some(Class::Combining);
+    }
+    Some(match scalar {
+        0x0300..=0x036F => Class::Combining,
+        0x3040..=0x30FF => Class::Kana,
+        0xFF61..=0xFF9F => Class::HalfWidthKana,
+        0xAC00..=0xD7A3 => Class::Hangul,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x3FFFF => Class::Han,
+        0xA0 | 0xA1 | 0xA3 | 0xA7 | 0xA9 | 0xAB | 0xAE | 0xB0 | 0xB7 | 0xBB | 0xBF => {
+            Class::Punctuation
+        }
+        0x2000..=0x206F | 0x20AC | 0x2116 | 0x3000..=0x303F | 0xFF01..=0xFF60 => Class::Punctuation,
+        _ => Class::Symbol,
+    })
+}
+
+/// Scores `current` following `previous` in text decoded by a candidate of
+/// the given kind. Positive means plausible.
+fn score(kind: Kind, previous: Class, current: Class, c: char) -> i64 {
+    let after_letter_like = matches!(
+        previous,
+        Class::AsciiLetter
+            | Class::Letter { .. }
+            | Class::Kana
+            | Class::HalfWidthKana
+            | Class::Hangul
+            | Class::Han
+    );
+    match current {
+        Class::Other | Class::Punctuation => 0,
+        Class::AsciiLetter => match previous {
+            Class::Letter {
+                script: Script::Latin,
+                ..
+            } => 0,
+            Class::Letter { .. }
+            | Class::Kana
+            | Class::HalfWidthKana
+            | Class::Hangul
+            | Class::Han => -3,
+            _ => 0,
+        },
+        Class::Letter { script, upper } => {
+            let mut score = if script_for_kind(kind) == Some(script) {
+                2
+            } else {
+                -3
+            };
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            let common = match script {
+                Script::Latin => match kind {
+                    Kind::Latin(common) => common,
+                    _ => "",
+                },
+                Script::Cyrillic => COMMON_CYRILLIC,
+                Script::Greek => COMMON_GREEK,
+                Script::Arabic => COMMON_ARABIC,
+                _ => "",
+            };
+            if !common.is_empty() && !common.contains(lower) {
+                score -= 2;
+            }
+            match previous {
+                Class::Letter {
+                    script: previous_script,
+                    upper: previous_upper,
+                } => {
+                    if previous_script != script || (upper && !previous_upper) {
+                        score -= 3;
+                    } else if upper {
+                        // All-caps words are rare in running text.
+                        score -= 2;
+                    } else if script == Script::Latin {
+                        // Adjacent non-ASCII letters are rarer than
+                        // non-ASCII letters among ASCII letters.
+                        score -= 1;
+                    }
+                }
+                Class::AsciiLetter => {
+                    if script != Script::Latin {
+                        score -= 3;
+                    } else if upper {
+                        score -= 2;
+                    }
+                }
+                Class::Kana | Class::HalfWidthKana | Class::Hangul | Class::Han => {
+                    score -= 3;
+                }
+                _ => {}
+            }
+            score
+        }
+        Class::Combining => {
+            if matches!(previous, Class::Letter { .. } | Class::AsciiLetter) {
+                1
+            } else {
+                -2
+            }
+        }
+        // The scores for CJK characters are doubled relative to the
+        // single-byte scripts, because these take two bytes.
+        Class::Kana => {
+            let score = if kind == Kind::Japanese { 6 } else { -2 };
+            if previous == Class::AsciiLetter {
+                score - 3
+            } else {
+                score
+            }
+        }
+        Class::HalfWidthKana => -2,
+        Class::Hangul => {
+            let score = if kind == Kind::Korean { 6 } else { -2 };
+            if previous == Class::AsciiLetter {
+                score - 3
+            } else {
+                score
+            }
+        }
+        Class::Han => {
+            let score = match kind {
+                Kind::Japanese => 2,
+                Kind::Simplified => {
+                    if c > '\u{FFFF}' {
+                        -2
+                    } else if gb2312_level1_hanzi_encode(c as u16).is_some() {
+                        4
+                    } else if gb2312_level2_hanzi_encode(c as u16).is_some() {
+                        2
+                    } else {
+                        -2
+                    }
+                }
+                Kind::Traditional => {
+                    if c <= '\u{FFFF}' && big5_level1_hanzi_encode(c as u16).is_some() {
+                        4
+                    } else {
+                        0
+                    }
+                }
+                _ => -2,
+            };
+            if after_letter_like && !matches!(previous, Class::Kana | Class::Han) {
+                score - 2
+            } else {
+                score
+            }
+        }
+        Class::Symbol => -1,
+    }
+}
+
+struct Candidate {
+    encoding: &'static Encoding,
+    kind: Kind,
+    decoder: Decoder,
+    score: i64,
+    previous: Class,
+    disqualified: bool,
+}
+
+impl Candidate {
+    fn feed(&mut self, buffer: &[u8], last: bool) {
+        if self.disqualified {
+            return;
+        }
+        let mut output = [0u16; 256];
+        let mut total_read = 0usize;
+        loop {
+            let (result, read, written) = self.decoder.decode_to_utf16_without_replacement(
+                &buffer[total_read..],
+                &mut output[..],
+                last,
+            );
+            total_read += read;
+            for c in char::decode_utf16(output[..written].iter().copied()) {
+                // The decoder never splits a surrogate pair.
+                let c = c.unwrap_or('\u{FFFD}');
+                match classify(c) {
+                    Some(class) => {
+                        self.score += score(self.kind, self.previous, class, c);
+                        self.previous = class;
+                    }
+                    None => {
+                        self.disqualified = true;
+                        return;
+                    }
+                }
+            }
+            match result {
+                DecoderResult::InputEmpty => {
+                    return;
+                }
+                DecoderResult::OutputFull => {}
+                DecoderResult::Malformed(_, _) => {
+                    self.disqualified = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// A statistical detector that guesses the encoding of unlabeled legacy
+/// content.
+///
+/// The input is decoded with each candidate encoding that the crate
+/// supports for legacy content, and pairs of adjacent decoded characters
+/// are scored according to how plausible they are in the kind of text
+/// (Latin, Cyrillic, Japanese, etc.) that the candidate is used for. A
+/// candidate is ruled out by a malformed byte sequence or by decoding to a
+/// C1 control or a Private Use Area character.
+///
+/// Available with the `detect` feature.
+///
+/// # Example
+/// ```
+/// use encoding_rs::*;
+///
+/// let (bytes, _, _) = WINDOWS_1251.encode("Съешь же ещё этих мягких французских булок");
+/// let mut detector = EncodingDetector::new();
+/// detector.feed(&bytes, true);
+/// assert_eq!(detector.guess(None, true), WINDOWS_1251);
+/// ```
+pub struct EncodingDetector {
+    candidates: [Candidate; 20],
+    utf8: Decoder,
+    utf8_valid: bool,
+    non_ascii_seen: bool,
+    esc_seen: bool,
+    closed: bool,
+}
+
+impl EncodingDetector {
+    /// Creates a new detector.
+    pub fn new() -> EncodingDetector {
+        EncodingDetector {
+            candidates: core::array::from_fn(|i| {
+                let (encoding, kind) = CANDIDATES[i];
+                Candidate {
+                    encoding,
+                    kind,
+                    decoder: encoding.new_decoder_without_bom_handling(),
+                    score: 0,
+                    previous: Class::Other,
+                    disqualified: false,
+                }
+            }),
+            utf8: UTF_8.new_decoder_without_bom_handling(),
+            utf8_valid: true,
+            non_ascii_seen: false,
+            esc_seen: false,
+            closed: false,
+        }
+    }
+
+    /// Feeds the next chunk of the stream to the detector. `last` must be
+    /// `true` for the last chunk and `false` otherwise.
+    ///
+    /// Returns `true` if the stream has contained at least one non-ASCII
+    /// byte so far.
+    ///
+    /// # Panics
+    ///
+    /// If called after a call with `last` set to `true`.
+    pub fn feed(&mut self, buffer: &[u8], last: bool) -> bool {
+        assert!(!self.closed, "Must not feed after the last buffer.");
+        self.closed = last;
+        if !self.non_ascii_seen {
+            let up_to = ascii_valid_up_to(buffer);
+            self.non_ascii_seen = up_to < buffer.len();
+            self.esc_seen |= buffer[..up_to].contains(&0x1B);
+        }
+        if self.utf8_valid {
+            let mut output = [0u16; 256];
+            let mut total_read = 0usize;
+            loop {
+                let (result, read, _) = self.utf8.decode_to_utf16_without_replacement(
+                    &buffer[total_read..],
+                    &mut output[..],
+                    last,
+                );
+                total_read += read;
+                match result {
+                    DecoderResult::InputEmpty => {
+                        break;
+                    }
+                    DecoderResult::OutputFull => {}
+                    DecoderResult::Malformed(_, _) => {
+                        self.utf8_valid = false;
+                        break;
+                    }
+                }
+            }
+        }
+        for candidate in self.candidates.iter_mut() {
+            candidate.feed(buffer, last);
+        }
+        self.non_ascii_seen
+    }
+
+    /// Returns the guess for the encoding of the stream fed so far.
+    ///
+    /// `tld` is the top-level domain (or a host name) that the content came
+    /// from, if known. It breaks ties and determines the result for content
+    /// that doesn't allow a guess, such as ASCII-only content, through
+    /// `Encoding::fallback_for_tld()`. Without a TLD, windows-1252 is the
+    /// fallback.
+    ///
+    /// If `allow_utf8` is `true` and the content is valid UTF-8 with at least
+    /// one non-ASCII character, UTF-8 is returned. If `allow_utf8` is
+    /// `false`, UTF-8 is never returned. Since the distinction between UTF-8
+    /// and legacy content is not statistical, content from the Web should
+    /// typically be treated with `allow_utf8` set to `false` for
+    /// compatibility.
+    pub fn guess(&self, tld: Option<&[u8]>, allow_utf8: bool) -> &'static Encoding {
+        let fallback = tld.map_or(WINDOWS_1252, Encoding::fallback_for_tld);
+        if !self.non_ascii_seen {
+            let iso_2022_jp = self
+                .candidates
+                .iter()
+                .find(|candidate| candidate.encoding == ISO_2022_JP);
+            if self.esc_seen
+                && iso_2022_jp
+                    .is_some_and(|candidate| !candidate.disqualified && candidate.score > 0)
+            {
+                return ISO_2022_JP;
+            }
+            return fallback;
+        }
+        if allow_utf8 && self.utf8_valid {
+            return UTF_8;
+        }
+        let mut best: Option<&Candidate> = None;
+        for candidate in self.candidates.iter() {
+            if candidate.disqualified {
+                continue;
+            }
+            match best {
+                Some(b)
+                    if b.score > candidate.score
+                        || (b.score == candidate.score && candidate.encoding != fallback) => {}
+                _ => {
+                    best = Some(candidate);
+                }
+            }
+        }
+        best.map_or(fallback, |candidate| candidate.encoding)
+    }
+}
+
+impl Default for EncodingDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::super::*;
+
+    fn check(encoding: &'static Encoding, text: &str) {
+        let (bytes, _, had_unmappables) = encoding.encode(text);
+        assert!(!had_unmappables);
+        let mut detector = EncodingDetector::new();
+        detector.feed(&bytes, true);
+        assert_eq!(detector.guess(None, false), encoding, "{}", text);
+        // Byte at a time
+        let mut detector = EncodingDetector::new();
+        for &b in bytes.iter() {
+            detector.feed(&[b], false);
+        }
+        detector.feed(b"", true);
+        assert_eq!(detector.guess(None, false), encoding, "{}", text);
+    }
+
+    #[test]
+    fn test_detect_latin() {
+        check(
+            WINDOWS_1252,
+            "Voix ambiguë d'un cœur qui, au zéphyr, préfère les jattes de kiwis.",
+        );
+        check(
+            WINDOWS_1252,
+            "Falsches Üben von Xylophonmusik quält jeden größeren Zwerg.",
+        );
+        check(WINDOWS_1250, "Příliš žluťoučký kůň úpěl ďábelské ódy.");
+        check(WINDOWS_1250, "Pchnąć w tę łódź jeża lub ośm skrzyń fig.");
+        check(
+            WINDOWS_1252,
+            "El niño comió una manzana y después salió a jugar con su perro en el jardín.",
+        );
+        check(
+            WINDOWS_1254,
+            "Türkiye Cumhuriyeti'nin başkenti Ankara'dır ve en kalabalık şehri İstanbul'dur.",
+        );
+    }
+
+    #[test]
+    fn test_detect_non_latin() {
+        check(
+            WINDOWS_1251,
+            "Съешь же ещё этих мягких французских булок, да выпей чаю.",
+        );
+        check(
+            KOI8_U,
+            "Съешь же ещё этих мягких французских булок, да выпей чаю.",
+        );
+        check(
+            IBM866,
+            "Съешь же ещё этих мягких французских булок, да выпей чаю.",
+        );
+        check(
+            WINDOWS_1253,
+            "Η γλώσσα είναι το σύστημα επικοινωνίας των ανθρώπων και μας επιτρέπει να μιλάμε.",
+        );
+        check(WINDOWS_1255, "דג סקרן שט בים מאוכזב ולפתע מצא חברה");
+        check(
+            WINDOWS_1256,
+            "اللغة العربية هي لغة رسمية في كل دول الوطن العربي وهي من أكثر اللغات انتشارا.",
+        );
+        check(WINDOWS_874, "เป็นมนุษย์สุดประเสริฐเลิศคุณค่า");
+    }
+
+    #[test]
+    fn test_detect_cjk() {
+        let japanese = "いろはにほへと ちりぬるを わかよたれそ つねならむ。日本語の文章です。";
+        check(SHIFT_JIS, japanese);
+        check(EUC_JP, japanese);
+        check(ISO_2022_JP, japanese);
+        check(EUC_KR, "다람쥐 헌 쳇바퀴에 타고파. 한국어 문장입니다.");
+        check(
+            GBK,
+            "我能吞下玻璃而不伤身体。这是一个简体中文的句子，我们在这里测试。",
+        );
+        check(
+            BIG5,
+            "我能吞下玻璃而不傷身體。這是一個繁體中文的句子，我們在這裡測試。",
+        );
+    }
+
+    #[test]
+    fn test_detect_utf8_and_fallbacks() {
+        let mut detector = EncodingDetector::new();
+        assert!(detector.feed("Grüße".as_bytes(), true));
+        assert_eq!(detector.guess(None, true), UTF_8);
+        assert_ne!(detector.guess(None, false), UTF_8);
+
+        let mut detector = EncodingDetector::new();
+        assert!(!detector.feed(b"plain ASCII", true));
+        assert_eq!(detector.guess(None, true), WINDOWS_1252);
+        assert_eq!(detector.guess(Some(b"jp"), true), SHIFT_JIS);
+        assert_eq!(detector.guess(Some(b"example.ru"), true), WINDOWS_1251);
+    }
+
+    #[test]
+    fn test_detect_tld_breaks_ties() {
+        // The bytes are the same in windows-1252 and windows-1257.
+        let (bytes, _, _) = WINDOWS_1252.encode("schön grün");
+        let mut detector = EncodingDetector::new();
+        detector.feed(&bytes, true);
+        assert_eq!(detector.guess(None, false), WINDOWS_1252);
+        assert_eq!(detector.guess(Some(b"lt"), false), WINDOWS_1257);
+    }
+}
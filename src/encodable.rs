@@ -0,0 +1,465 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+
+#[cfg(feature = "alloc")]
+use crate::data::{BIG5_LOW_BITS, big5_is_astral, big5_low_bits};
+#[cfg(feature = "alloc")]
+use core::ops::RangeInclusive;
+
+/// An iterator over the characters of a string that an encoding cannot
+/// encode paired with their byte offsets in the string.
+///
+/// Returned by `Encoding::unmappable_chars()`.
+pub struct UnmappableChars<'a> {
+    variant: VariantEncoder,
+    ascii_valid_up_to: fn(&[u8]) -> usize,
+    string: &'a str,
+    offset: usize,
+}
+
+impl<'a> UnmappableChars<'a> {
+    pub(crate) fn new(encoding: &'static Encoding, string: &'a str) -> UnmappableChars<'a> {
+        let output_encoding = encoding.output_encoding();
+        UnmappableChars {
+            variant: output_encoding.new_encoder().variant,
+            ascii_valid_up_to: if output_encoding == ISO_2022_JP {
+                iso_2022_jp_ascii_valid_up_to
+            } else {
+                ascii_valid_up_to
+            },
+            string,
+            // UTF-8 can encode everything.
+            offset: if output_encoding == UTF_8 {
+                string.len()
+            } else {
+                0
+            },
+        }
+    }
+}
+
+impl<'a> Iterator for UnmappableChars<'a> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<(usize, char)> {
+        loop {
+            self.offset += (self.ascii_valid_up_to)(&self.string.as_bytes()[self.offset..]);
+            let c = self.string[self.offset..].chars().next()?;
+            let position = self.offset;
+            self.offset += c.len_utf8();
+            if !self.variant.can_encode(c) {
+                return Some((position, c));
+            }
+        }
+    }
+}
+
+impl<'a> core::iter::FusedIterator for UnmappableChars<'a> {}
+
+/// Computes the length of `string` encoded with `encoding` without keeping
+/// the output. Unmappable characters don't count towards the length.
+pub(crate) fn encoded_length(encoding: &'static Encoding, string: &str) -> usize {
+    if encoding.output_encoding() == UTF_8 {
+        return string.len();
+    }
+    let mut encoder = encoding.new_encoder();
+    let mut buffer = [0u8; 1024];
+    let mut total_read = 0usize;
+    let mut total_written = 0usize;
+    loop {
+        let (result, read, written) = encoder.encode_from_utf8_without_replacement(
+            &string[total_read..],
+            &mut buffer[..],
+            true,
+        );
+        total_read += read;
+        total_written += written;
+        if result == EncoderResult::InputEmpty {
+            return total_written;
+        }
+    }
+}
+
+/// The set of Unicode scalar values that an encoding can encode.
+///
+/// The set is stored as a sorted list of disjoint ranges, which is compact
+/// for the repertoires of the encodings in the Encoding Standard.
+///
+/// Returned by `Encoding::encodable_set()`.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodableSet {
+    /// Inclusive ranges in ascending order. Adjacent ranges are merged.
+    ranges: Vec<(u32, u32)>,
+}
+
+#[cfg(feature = "alloc")]
+impl EncodableSet {
+    pub(crate) fn new(encoding: &'static Encoding) -> EncodableSet {
+        let encoding = encoding.output_encoding();
+        let mut set = EncodableSet { ranges: Vec::new() };
+        if encoding == UTF_8 {
+            set.push_range(0, 0xD7FF);
+            set.push_range(0xE000, 0x10FFFF);
+            return set;
+        }
+        if let VariantEncoding::SingleByte(table, _, _, _) = encoding.variant {
+            // Zero marks an unmapped byte.
+            let mut upper: Vec<u32> = table
+                .iter()
+                .filter(|&&unit| unit != 0)
+                .map(|&unit| u32::from(unit))
+                .collect();
+            upper.sort_unstable();
+            set.push_range(0, 0x7F);
+            for scalar in upper {
+                set.push(scalar);
+            }
+            return set;
+        }
+        let variant = encoding.new_encoder().variant;
+        for c in '\u{0}'..='\u{FFFF}' {
+            if variant.can_encode(c) {
+                set.push(c as u32);
+            }
+        }
+        // The astral characters that the multi-byte encoders map.
+        if encoding == GB18030 {
+            set.push_range(0x10000, 0x10FFFF);
+        } else if encoding == BIG5 {
+            // Only the astral characters in the index are candidates.
+            let mut astral: Vec<u32> = (0..BIG5_LOW_BITS.len())
+                .filter(|&rebased_pointer| big5_is_astral(rebased_pointer))
+                .map(|rebased_pointer| 0x20000 | u32::from(big5_low_bits(rebased_pointer)))
+                .filter(|&scalar| char::from_u32(scalar).is_some_and(|c| variant.can_encode(c)))
+                .collect();
+            astral.sort_unstable();
+            for scalar in astral {
+                set.push(scalar);
+            }
+        }
+        set
+    }
+
+    fn push(&mut self, scalar: u32) {
+        self.push_range(scalar, scalar);
+    }
+
+    /// Appends a range that doesn't start before the last range.
+    fn push_range(&mut self, start: u32, end: u32) {
+        if let Some(last) = self.ranges.last_mut()
+            && start <= last.1 + 1
+        {
+            if end > last.1 {
+                last.1 = end;
+            }
+            return;
+        }
+        self.ranges.push((start, end));
+    }
+
+    /// Checks whether the set contains `c`.
+    pub fn contains(&self, c: char) -> bool {
+        let scalar = c as u32;
+        self.ranges
+            .binary_search_by(|&(start, end)| {
+                if end < scalar {
+                    Ordering::Less
+                } else if start > scalar {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    /// Returns the number of scalar values in the set.
+    pub fn len(&self) -> usize {
+        self.ranges
+            .iter()
+            .map(|&(start, end)| (end - start + 1) as usize)
+            .sum()
+    }
+
+    /// Checks whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns an iterator over the maximal ranges of consecutive scalar
+    /// values in the set in ascending order.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<char>> + '_ {
+        self.ranges.iter().map(|&(start, end)| {
+            // The ranges never include surrogates.
+            char::from_u32(start).unwrap()..=char::from_u32(end).unwrap()
+        })
+    }
+
+    /// Checks whether every scalar value in `other` is also in this set.
+    pub fn is_superset_of(&self, other: &EncodableSet) -> bool {
+        // Since the ranges are maximal, each range of `other` has to fit
+        // within a single range of `self`.
+        other.ranges.iter().all(|&(start, end)| {
+            let i = self.ranges.partition_point(|&(_, e)| e < start);
+            self.ranges
+                .get(i)
+                .is_some_and(|&(s, e)| s <= start && end <= e)
+        })
+    }
+
+    /// Checks whether every scalar value in this set is also in `other`.
+    pub fn is_subset_of(&self, other: &EncodableSet) -> bool {
+        other.is_superset_of(self)
+    }
+
+    /// Returns the scalar values that are in either set.
+    pub fn union(&self, other: &EncodableSet) -> EncodableSet {
+        let mut ranges: Vec<(u32, u32)> = self
+            .ranges
+            .iter()
+            .chain(other.ranges.iter())
+            .copied()
+            .collect();
+        ranges.sort_unstable();
+        let mut set = EncodableSet { ranges: Vec::new() };
+        for (start, end) in ranges {
+            set.push_range(start, end);
+        }
+        set
+    }
+
+    /// Returns the scalar values that are in both sets.
+    pub fn intersection(&self, other: &EncodableSet) -> EncodableSet {
+        let mut set = EncodableSet { ranges: Vec::new() };
+        let mut i = 0;
+        let mut j = 0;
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_start, a_end) = self.ranges[i];
+            let (b_start, b_end) = other.ranges[j];
+            let start = a_start.max(b_start);
+            let end = a_end.min(b_end);
+            if start <= end {
+                set.push_range(start, end);
+            }
+            if a_end < b_end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        set
+    }
+
+    /// Returns the scalar values that are in this set but not in `other`.
+    pub fn difference(&self, other: &EncodableSet) -> EncodableSet {
+        let mut set = EncodableSet { ranges: Vec::new() };
+        let mut j = 0;
+        for &(start, end) in self.ranges.iter() {
+            let mut start = start;
+            while j < other.ranges.len() && other.ranges[j].1 < start {
+                j += 1;
+            }
+            let mut k = j;
+            while start <= end {
+                match other.ranges.get(k) {
+                    Some(&(b_start, b_end)) if b_start <= end => {
+                        if b_start > start {
+                            set.push_range(start, b_start - 1);
+                        }
+                        if b_end >= end {
+                            break;
+                        }
+                        start = b_end + 1;
+                        k += 1;
+                    }
+                    _ => {
+                        set.push_range(start, end);
+                        break;
+                    }
+                }
+            }
+        }
+        set
+    }
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_unmappable_chars() {
+        let string = "a\u{E4}\u{3042}b\u{1F4A9}\u{20AC}";
+        assert_eq!(
+            WINDOWS_1252.unmappable_chars(string).collect::<Vec<_>>(),
+            [(3, '\u{3042}'), (7, '\u{1F4A9}')]
+        );
+        assert_eq!(
+            SHIFT_JIS.unmappable_chars(string).collect::<Vec<_>>(),
+            [(1, '\u{E4}'), (7, '\u{1F4A9}'), (11, '\u{20AC}')]
+        );
+        assert_eq!(GB18030.unmappable_chars(string).count(), 0);
+        assert_eq!(UTF_16LE.unmappable_chars(string).count(), 0);
+        assert_eq!(
+            ISO_2022_JP
+                .unmappable_chars("a\u{1B}b\u{A5}\u{3042}\u{0E}")
+                .collect::<Vec<_>>(),
+            [(1, '\u{1B}'), (8, '\u{0E}')]
+        );
+    }
+
+    #[test]
+    fn test_best_for() {
+        let candidates = [ISO_2022_JP, SHIFT_JIS, UTF_8];
+        assert_eq!(Encoding::best_for("abc", &candidates), ISO_2022_JP);
+        assert_eq!(Encoding::best_for("\u{3042}", &candidates), ISO_2022_JP);
+        assert_eq!(Encoding::best_for("\u{FF61}\u{80}", &candidates), SHIFT_JIS);
+        assert_eq!(Encoding::best_for("\u{1F4A9}", &candidates), UTF_8);
+        assert_eq!(Encoding::best_for("\u{1F4A9}", &[WINDOWS_1252]), UTF_8);
+        assert_eq!(Encoding::best_for("a", &[]), UTF_8);
+        assert_eq!(Encoding::best_for("a", &[UTF_16LE, WINDOWS_1252]), UTF_8);
+    }
+
+    #[test]
+    fn test_smallest_for() {
+        let candidates = [ISO_2022_JP, SHIFT_JIS, UTF_8];
+        assert_eq!(Encoding::smallest_for("abc", &candidates), ISO_2022_JP);
+        assert_eq!(Encoding::smallest_for("\u{3042}", &candidates), SHIFT_JIS);
+        assert_eq!(
+            Encoding::smallest_for("\u{E4}\u{E4}\u{E4}", &[UTF_8, WINDOWS_1252]),
+            WINDOWS_1252
+        );
+        assert_eq!(Encoding::smallest_for("\u{1F4A9}", &candidates), UTF_8);
+        assert_eq!(encoded_length(ISO_2022_JP, "a\u{3042}b"), 1 + 3 + 2 + 3 + 1);
+        let long = "\u{3042}a".repeat(1000);
+        assert_eq!(encoded_length(EUC_JP, &long), 3000);
+    }
+
+    #[test]
+    fn test_encodable_set() {
+        let windows_1253 = WINDOWS_1253.encodable_set();
+        assert_eq!(windows_1253.len(), 256 - 3);
+        assert!(windows_1253.contains('\u{03A3}'));
+        assert!(!windows_1253.contains('\u{E4}'));
+        assert_eq!(windows_1253.ranges().next(), Some('\u{0}'..='\u{7F}'));
+
+        let gbk = GBK.encodable_set();
+        let gb18030 = GB18030.encodable_set();
+        assert!(gb18030.is_superset_of(&gbk));
+        assert!(!gbk.is_superset_of(&gb18030));
+        assert!(gbk.is_subset_of(&gb18030));
+        assert!(UTF_8.encodable_set().is_superset_of(&gb18030));
+        assert_eq!(gb18030.len(), 0x110000 - 0x800 - 1);
+        assert!(!gb18030.contains('\u{E5E5}'));
+        assert_eq!(UTF_16BE.encodable_set(), UTF_8.encodable_set());
+
+        let big5 = BIG5.encodable_set();
+        assert!(big5.contains('\u{2008A}'));
+        assert!(!big5.contains('\u{1F4A9}'));
+
+        let shift_jis = SHIFT_JIS.encodable_set();
+        let iso_2022_jp = ISO_2022_JP.encodable_set();
+        let difference = shift_jis.difference(&iso_2022_jp);
+        assert!(difference.contains('\u{80}'));
+        assert!(difference.contains('\u{0E}'));
+        assert!(!difference.contains('\u{3042}'));
+        let intersection = shift_jis.intersection(&iso_2022_jp);
+        assert_eq!(intersection.len() + difference.len(), shift_jis.len());
+        assert!(intersection.is_subset_of(&iso_2022_jp));
+        let union = shift_jis.union(&iso_2022_jp);
+        assert_eq!(
+            union.len(),
+            shift_jis.len() + iso_2022_jp.difference(&shift_jis).len()
+        );
+        assert!(union.difference(&union).is_empty());
+    }
+
+    #[test]
+    fn test_encodable_set_matches_can_encode() {
+        // The runs of scalar values between the characters that fail
+        // `can_encode()` must be the ranges of the set. Surrogates split the
+        // runs, because they aren't scalar values.
+        fn push_run(runs: &mut Vec<(u32, u32)>, start: u32, end: u32) {
+            for (start, end) in [(start, end.min(0xD800)), (start.max(0xE000), end)] {
+                if start < end {
+                    runs.push((start, end - 1));
+                }
+            }
+        }
+        let all: String = ('\u{0}'..=char::MAX).collect();
+        for encoding in Encoding::all() {
+            let mut runs = Vec::new();
+            let mut start = 0;
+            for (_, c) in encoding.unmappable_chars(&all) {
+                push_run(&mut runs, start, c as u32);
+                start = c as u32 + 1;
+            }
+            push_run(&mut runs, start, 0x110000);
+            let ranges: Vec<(u32, u32)> = encoding
+                .encodable_set()
+                .ranges()
+                .map(|range| (*range.start() as u32, *range.end() as u32))
+                .collect();
+            assert_eq!(ranges, runs, "{}", encoding.name());
+        }
+    }
+
+    #[test]
+    fn test_can_encode_matches_encoder() {
+        // The predicates must agree with the encoders for every scalar
+        // value, including the decoder outputs that the Encoding Standard
+        // excludes from encode. `unmappable_chars()` yields the characters
+        // that fail the predicate, and the encoder stops at each unmappable
+        // character and resumes after it.
+        let all: String = ('\u{0}'..=char::MAX).collect();
+        for &encoding in Encoding::all() {
+            let mut unmappables = encoding.unmappable_chars(&all);
+            let mut encoder = encoding.new_encoder();
+            let mut out = vec![
+                0u8;
+                encoder
+                    .max_buffer_length_from_utf8_without_replacement(all.len())
+                    .unwrap()
+            ];
+            let mut read = 0;
+            loop {
+                let (result, read_now, _) =
+                    encoder.encode_from_utf8_without_replacement(&all[read..], &mut out, true);
+                read += read_now;
+                match result {
+                    EncoderResult::InputEmpty => break,
+                    EncoderResult::Unmappable(reported) => {
+                        let (offset, c) = unmappables.next().unwrap();
+                        assert_eq!(offset + c.len_utf8(), read, "{} {:?}", encoding.name(), c);
+                        // ISO-2022-JP reports U+000E, U+000F and U+001B as
+                        // U+FFFD.
+                        let expected = if encoding == ISO_2022_JP
+                            && matches!(c, '\u{0E}' | '\u{0F}' | '\u{1B}')
+                        {
+                            '\u{FFFD}'
+                        } else {
+                            c
+                        };
+                        assert_eq!(reported, expected, "{}", encoding.name());
+                    }
+                    EncoderResult::OutputFull => unreachable!(),
+                }
+            }
+            assert_eq!(unmappables.next(), None, "{}", encoding.name());
+        }
+    }
+}
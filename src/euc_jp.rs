@@ -266,6 +266,34 @@ impl EucJpEncoder {
         byte_length.checked_add(1)
     }
 
+    pub fn can_encode(&self, c: char) -> bool {
+        if c <= '\u{7F}' {
+            return true;
+        }
+        if c > '\u{FFFF}' {
+            return false;
+        }
+        let bmp = c as u16;
+        if bmp.wrapping_sub(0x3041) < 0x53 {
+            true
+        } else if in_inclusive_range16(bmp, 0x4E00, 0x9FA0) {
+            encode_kanji(bmp).is_some()
+        } else {
+            bmp.wrapping_sub(0x30A1) < 0x56
+                || bmp.wrapping_sub(0x3000) < 3
+                || bmp == 0xA5
+                || bmp == 0x203E
+                || in_inclusive_range16(bmp, 0xFF61, 0xFF9F)
+                || bmp == 0x2212
+                || jis0208_range_encode(bmp).is_some()
+                || in_inclusive_range16(bmp, 0xFA0E, 0xFA2D)
+                || bmp == 0xF929
+                || bmp == 0xF9DC
+                || ibm_symbol_encode(bmp).is_some()
+                || jis0208_symbol_encode(bmp).is_some()
+        }
+    }
+
     ascii_compatible_bmp_encoder_functions!(
         {
             // Lunde says 60% Hiragana, 30% Kanji, 10% Katakana
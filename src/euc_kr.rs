@@ -308,6 +308,24 @@ impl EucKrEncoder {
         byte_length.checked_add(1)
     }
 
+    pub fn can_encode(&self, c: char) -> bool {
+        if c <= '\u{7F}' {
+            return true;
+        }
+        if c > '\u{FFFF}' {
+            return false;
+        }
+        let bmp = c as u16;
+        if bmp.wrapping_sub(0xAC00) < (0xD7A4 - 0xAC00) {
+            true
+        } else if in_range16(bmp, 0x33DE, 0xFF01) {
+            (in_range16(bmp, 0x4E00, 0x9F9D) || in_range16(bmp, 0xF900, 0xFA0C))
+                && ksx1001_encode_hanja(bmp).is_some()
+        } else {
+            ksx1001_encode_misc(bmp).is_some()
+        }
+    }
+
     ascii_compatible_bmp_encoder_functions!(
         {
             let bmp_minus_hangul_start = bmp.wrapping_sub(0xAC00);
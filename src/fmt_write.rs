@@ -0,0 +1,260 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use core::fmt;
+
+/// Enough for one character or NCR together with the ISO-2022-JP escape
+/// sequences around it.
+const CHARACTER_BUFFER_LENGTH: usize = 32;
+
+enum Output<'a> {
+    #[cfg(feature = "alloc")]
+    Vec(&'a mut Vec<u8>),
+    Slice(&'a mut [u8]),
+}
+
+/// Encodes the text written to it via `core::fmt::Write`, so that
+/// `write!()` formats directly into the target encoding.
+///
+/// The output goes either to the end of a `Vec<u8>`, which grows as
+/// needed, or to a fixed `&mut [u8]`. Unmappable characters are replaced
+/// with HTML decimal numeric character references and `had_replacements()`
+/// tells whether that happened.
+///
+/// When the slice runs out of space, the output ends at a character
+/// boundary and the write fails with `core::fmt::Error`. All later writes
+/// fail, too.
+///
+/// `finish()` must be called at the end of the text for ISO-2022-JP to
+/// return to the ASCII state.
+///
+/// # Example
+/// ```
+/// use core::fmt::Write;
+/// use encoding_rs::*;
+///
+/// let mut bytes = Vec::new();
+/// let mut writer = EncodeFmtWriter::new_with_vec(SHIFT_JIS.new_encoder(), &mut bytes);
+/// write!(writer, "{}円 {}", 100, '\u{1F600}').unwrap();
+/// assert!(writer.had_replacements());
+/// writer.finish().unwrap();
+/// assert_eq!(bytes, b"100\x89\x7E &#128512;");
+/// ```
+pub struct EncodeFmtWriter<'a> {
+    encoder: Encoder,
+    output: Output<'a>,
+    written: usize,
+    had_replacements: bool,
+    full: bool,
+}
+
+impl<'a> EncodeFmtWriter<'a> {
+    /// Appends the encoded text to `vec`.
+    ///
+    /// Available to Rust only and only with the `alloc` feature enabled (enabled
+    /// by default).
+    #[cfg(feature = "alloc")]
+    pub fn new_with_vec(encoder: Encoder, vec: &'a mut Vec<u8>) -> EncodeFmtWriter<'a> {
+        EncodeFmtWriter {
+            encoder,
+            output: Output::Vec(vec),
+            written: 0,
+            had_replacements: false,
+            full: false,
+        }
+    }
+
+    /// Writes the encoded text to the start of `buf`.
+    pub fn new_with_slice(encoder: Encoder, buf: &'a mut [u8]) -> EncodeFmtWriter<'a> {
+        EncodeFmtWriter {
+            encoder,
+            output: Output::Slice(buf),
+            written: 0,
+            had_replacements: false,
+            full: false,
+        }
+    }
+
+    /// The encoding of the encoder.
+    #[inline]
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoder.encoding()
+    }
+
+    /// Whether unmappable characters have been replaced with numeric
+    /// character references.
+    #[inline]
+    pub fn had_replacements(&self) -> bool {
+        self.had_replacements
+    }
+
+    /// The number of bytes written so far.
+    #[inline]
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Ends the text, writes the escape sequence back to ASCII for
+    /// ISO-2022-JP and returns the total number of bytes written.
+    ///
+    /// Fails if the slice doesn't have space for the escape sequence or if
+    /// an earlier write has failed.
+    pub fn finish(mut self) -> Result<usize, fmt::Error> {
+        self.encode("", true)?;
+        Ok(self.written)
+    }
+
+    fn encode(&mut self, src: &str, last: bool) -> fmt::Result {
+        if self.full {
+            return Err(fmt::Error);
+        }
+        let mut src = src;
+        match &mut self.output {
+            #[cfg(feature = "alloc")]
+            Output::Vec(vec) => loop {
+                let needed = self
+                    .encoder
+                    .max_buffer_length_from_utf8_if_no_unmappables(src.len())
+                    .unwrap_or(src.len());
+                vec.reserve(core::cmp::max(needed, NCR_EXTRA));
+                let old_len = vec.len();
+                let (result, read, replaced) = self.encoder.encode_from_utf8_to_vec(src, vec, last);
+                self.written += vec.len() - old_len;
+                self.had_replacements |= replaced;
+                src = &src[read..];
+                if result == CoderResult::InputEmpty {
+                    return Ok(());
+                }
+            },
+            Output::Slice(buf) => loop {
+                let (result, read, written, replaced) =
+                    self.encoder
+                        .encode_from_utf8(src, &mut buf[self.written..], last);
+                self.written += written;
+                self.had_replacements |= replaced;
+                src = &src[read..];
+                if result == CoderResult::InputEmpty {
+                    return Ok(());
+                }
+                // The encoder wants space for an NCR, so the next character
+                // may still fit. Encode it separately to find out.
+                let char_len = src.chars().next().map_or(0, char::len_utf8);
+                let mut character = [0u8; CHARACTER_BUFFER_LENGTH];
+                let (result, _, written, replaced) = self.encoder.encode_from_utf8(
+                    &src[..char_len],
+                    &mut character,
+                    last && char_len == src.len(),
+                );
+                debug_assert_eq!(result, CoderResult::InputEmpty);
+                let Some(dst) = buf.get_mut(self.written..self.written + written) else {
+                    self.full = true;
+                    return Err(fmt::Error);
+                };
+                dst.copy_from_slice(&character[..written]);
+                self.written += written;
+                self.had_replacements |= replaced;
+                src = &src[char_len..];
+                if src.is_empty() {
+                    return Ok(());
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Write for EncodeFmtWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.encode(s, false)
+    }
+}
+
+impl fmt::Debug for EncodeFmtWriter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EncodeFmtWriter")
+            .field("encoder", &self.encoder)
+            .field("written", &self.written)
+            .finish_non_exhaustive()
+    }
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn test_encode_fmt_writer_vec() {
+        let mut bytes = b"prefix ".to_vec();
+        let mut writer = EncodeFmtWriter::new_with_vec(ISO_2022_JP.new_encoder(), &mut bytes);
+        let number = 42;
+        write!(writer, "日本:{number}").unwrap();
+        assert!(!writer.had_replacements());
+        assert_eq!(writer.finish(), Ok(13));
+        assert_eq!(bytes, b"prefix \x1B$BF|K\\\x1B(B:42");
+
+        let mut bytes = Vec::new();
+        let mut writer = EncodeFmtWriter::new_with_vec(WINDOWS_1252.new_encoder(), &mut bytes);
+        write!(writer, "{}", "\u{3042}".repeat(100)).unwrap();
+        assert!(writer.had_replacements());
+        assert_eq!(writer.finish(), Ok(800));
+        assert_eq!(bytes, "&#12354;".repeat(100).as_bytes());
+    }
+
+    #[test]
+    fn test_encode_fmt_writer_slice() {
+        let mut buf = [0u8; 16];
+        let mut writer = EncodeFmtWriter::new_with_slice(SHIFT_JIS.new_encoder(), &mut buf);
+        writer.write_char('a').unwrap();
+        writer.write_char('\u{3042}').unwrap();
+        writer.write_str("b").unwrap();
+        assert_eq!(writer.finish(), Ok(4));
+        assert_eq!(&buf[..4], b"a\x82\xA0b");
+
+        // Space runs out at a character boundary and stays out.
+        let mut buf = [0u8; 6];
+        let mut writer = EncodeFmtWriter::new_with_slice(SHIFT_JIS.new_encoder(), &mut buf);
+        assert!(write!(writer, "\u{3042}\u{3042}\u{3042}\u{3042}").is_err());
+        assert_eq!(writer.written(), 6);
+        assert!(write!(writer, "").is_err());
+        assert_eq!(buf, *b"\x82\xA0\x82\xA0\x82\xA0");
+
+        let mut buf = [0u8; 9];
+        let mut writer = EncodeFmtWriter::new_with_slice(WINDOWS_1252.new_encoder(), &mut buf);
+        assert!(write!(writer, "ab\u{3042}").is_err());
+        assert_eq!(writer.written(), 2);
+        assert!(!writer.had_replacements());
+    }
+
+    #[test]
+    fn test_encode_fmt_writer_slice_iso_2022_jp() {
+        let expected = b"\x1B$BF|K\\\x1B(B";
+        let mut buf = [0u8; 10];
+        let mut writer = EncodeFmtWriter::new_with_slice(ISO_2022_JP.new_encoder(), &mut buf);
+        write!(writer, "日本").unwrap();
+        assert_eq!(writer.finish(), Ok(10));
+        assert_eq!(&buf, expected);
+
+        // No space for the escape sequence back to ASCII.
+        let mut buf = [0u8; 9];
+        let mut writer = EncodeFmtWriter::new_with_slice(ISO_2022_JP.new_encoder(), &mut buf);
+        write!(writer, "日本").unwrap();
+        assert_eq!(writer.finish(), Err(core::fmt::Error));
+
+        let mut buf = [0u8; 17];
+        let mut writer = EncodeFmtWriter::new_with_slice(ISO_2022_JP.new_encoder(), &mut buf);
+        write!(writer, "日\u{1F600}").unwrap();
+        assert!(writer.had_replacements());
+        assert_eq!(writer.finish(), Ok(17));
+        assert_eq!(&buf, b"\x1B$BF|\x1B(B&#128512;");
+    }
+}
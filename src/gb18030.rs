@@ -493,6 +493,23 @@ impl Gb18030Encoder {
         }
     }
 
+    pub fn can_encode(&self, c: char) -> bool {
+        if c <= '\u{7F}' {
+            return true;
+        }
+        if c > '\u{FFFF}' {
+            return self.extended;
+        }
+        let bmp = c as u16;
+        if in_range16(bmp, 0x4E00, 0x9FA6) {
+            true
+        } else if bmp == 0xE5E5 {
+            false
+        } else {
+            self.extended || bmp == 0x20AC || gbk_encode_non_unified(bmp).is_some()
+        }
+    }
+
     ascii_compatible_encoder_functions!(
         {
             let bmp_minus_unified_start = bmp.wrapping_sub(0x4E00);
@@ -0,0 +1,724 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adapters between `std::io` and the streaming API.
+
+use super::*;
+use std::io::{self, BufRead, Read, Write};
+
+/// The size of the internal buffers.
+pub(crate) const IO_BUFFER_LENGTH: usize = 8192;
+
+/// The decoding state shared by `DecodeReader` and the async reader. The
+/// owner supplies input whenever `wants_input()` returns `true`.
+pub(crate) struct DecodeState {
+    decoder: Decoder,
+    replace: bool,
+    input: Vec<u8>,
+    input_start: usize,
+    input_end: usize,
+    output: Vec<u8>,
+    output_start: usize,
+    output_end: usize,
+    eof: bool,
+    finished: bool,
+    pending_error: bool,
+}
+
+impl DecodeState {
+    pub(crate) fn new(decoder: Decoder, replace: bool) -> DecodeState {
+        DecodeState {
+            decoder,
+            replace,
+            input: vec![0; IO_BUFFER_LENGTH],
+            input_start: 0,
+            input_end: 0,
+            output: vec![0; IO_BUFFER_LENGTH],
+            output_start: 0,
+            output_end: 0,
+            eof: false,
+            finished: false,
+            pending_error: false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    /// Whether `fill()` stopped because it needs more input.
+    #[inline]
+    pub(crate) fn wants_input(&self) -> bool {
+        self.output_start == self.output_end
+            && !self.pending_error
+            && !self.finished
+            && self.input_start == self.input_end
+            && !self.eof
+    }
+
+    /// The buffer to read input into.
+    #[inline]
+    pub(crate) fn input_buffer(&mut self) -> &mut [u8] {
+        &mut self.input
+    }
+
+    /// Records that `read` bytes were read into `input_buffer()`. Zero
+    /// means the end of the stream.
+    #[inline]
+    pub(crate) fn set_input(&mut self, read: usize) {
+        self.input_start = 0;
+        self.input_end = read;
+        self.eof = read == 0;
+    }
+
+    /// Decodes until there is output, the stream has ended or more input
+    /// is needed.
+    pub(crate) fn fill(&mut self) -> io::Result<()> {
+        while self.output_start == self.output_end {
+            if self.pending_error {
+                self.pending_error = false;
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "malformed byte sequence",
+                ));
+            }
+            if self.finished || (self.input_start == self.input_end && !self.eof) {
+                break;
+            }
+            let src = &self.input[self.input_start..self.input_end];
+            let (read, written) = if self.replace {
+                let (result, read, written, _) =
+                    self.decoder.decode_to_utf8(src, &mut self.output, self.eof);
+                if self.eof && result == CoderResult::InputEmpty {
+                    self.finished = true;
+                }
+                (read, written)
+            } else {
+                let (result, read, written) = self.decoder.decode_to_utf8_without_replacement(
+                    src,
+                    &mut self.output,
+                    self.eof,
+                );
+                match result {
+                    DecoderResult::InputEmpty => {
+                        if self.eof {
+                            self.finished = true;
+                        }
+                    }
+                    DecoderResult::OutputFull => {}
+                    DecoderResult::Malformed(_, _) => {
+                        self.pending_error = true;
+                    }
+                }
+                (read, written)
+            };
+            self.input_start += read;
+            self.output_start = 0;
+            self.output_end = written;
+        }
+        Ok(())
+    }
+
+    /// The decoded output not consumed yet. Empty at the end of the stream.
+    #[inline]
+    pub(crate) fn output(&self) -> &[u8] {
+        &self.output[self.output_start..self.output_end]
+    }
+
+    #[inline]
+    pub(crate) fn consume(&mut self, amt: usize) {
+        self.output_start = core::cmp::min(self.output_start + amt, self.output_end);
+    }
+}
+
+/// Wraps a `std::io::Read` and presents its bytes decoded into UTF-8 via
+/// `std::io::Read` and `std::io::BufRead`.
+///
+/// The encoding and BOM handling are those of the `Decoder` given to the
+/// constructor. Use `Encoding::new_decoder()` for BOM sniffing.
+///
+/// When the wrapped reader reports the end of the stream, the decoder is
+/// called with `last` set to `true`, so a truncated byte sequence at the
+/// end results in a REPLACEMENT CHARACTER (or an error without
+/// replacement). Errors from the wrapped reader are passed through.
+///
+/// # Example
+/// ```
+/// use encoding_rs::*;
+/// use std::io::Read;
+///
+/// let bytes = b"\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd";
+/// let mut reader = DecodeReader::new(&bytes[..], SHIFT_JIS.new_decoder());
+/// let mut string = String::new();
+/// reader.read_to_string(&mut string).unwrap();
+/// assert_eq!(string, "こんにちは");
+/// ```
+pub struct DecodeReader<R> {
+    inner: R,
+    state: DecodeState,
+}
+
+impl<R: Read> DecodeReader<R> {
+    /// Wraps `inner` so that malformed sequences are replaced with the
+    /// REPLACEMENT CHARACTER.
+    pub fn new(inner: R, decoder: Decoder) -> DecodeReader<R> {
+        DecodeReader {
+            inner,
+            state: DecodeState::new(decoder, true),
+        }
+    }
+
+    /// Wraps `inner` so that a malformed sequence results in an error of
+    /// the kind `std::io::ErrorKind::InvalidData`.
+    ///
+    /// The output preceding the malformed sequence is returned before the
+    /// error. After the error, reading continues after the malformed
+    /// sequence.
+    pub fn new_without_replacement(inner: R, decoder: Decoder) -> DecodeReader<R> {
+        DecodeReader {
+            inner,
+            state: DecodeState::new(decoder, false),
+        }
+    }
+
+    /// The encoding of the decoder. BOM sniffing can change the return
+    /// value during reading.
+    #[inline]
+    pub fn encoding(&self) -> &'static Encoding {
+        self.state.decoder().encoding()
+    }
+
+    /// Returns a reference to the wrapped reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped reader.
+    ///
+    /// Reading from the wrapped reader directly desynchronizes this reader.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped reader. Buffered data is lost.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> BufRead for DecodeReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        loop {
+            self.state.fill()?;
+            if !self.state.wants_input() {
+                return Ok(self.state.output());
+            }
+            let read = self.inner.read(self.state.input_buffer())?;
+            self.state.set_input(read);
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.state.consume(amt);
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = core::cmp::min(available.len(), buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R> core::fmt::Debug for DecodeReader<R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("DecodeReader")
+            .field("decoder", &self.state.decoder)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The error payload of the `std::io::Error` that `EncodeWriter` returns
+/// for an unmappable character when not using replacement.
+///
+/// # Example
+/// ```
+/// use encoding_rs::*;
+/// use std::io::Write;
+///
+/// let mut writer = EncodeWriter::new_without_replacement(Vec::new(), SHIFT_JIS.new_encoder());
+/// let err = writer.write_str("a\u{1F4A9}").unwrap_err();
+/// let unmappable = err.get_ref().unwrap().downcast_ref::<UnmappableError>().unwrap();
+/// assert_eq!(unmappable.character(), '\u{1F4A9}');
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnmappableError {
+    encoding: &'static Encoding,
+    character: char,
+}
+
+impl UnmappableError {
+    /// The character that couldn't be encoded.
+    #[inline]
+    pub fn character(&self) -> char {
+        self.character
+    }
+
+    /// The encoding that couldn't encode the character.
+    #[inline]
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+}
+
+impl core::fmt::Display for UnmappableError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "U+{:04X} cannot be encoded in {}",
+            u32::from(self.character),
+            self.encoding.name()
+        )
+    }
+}
+
+impl std::error::Error for UnmappableError {}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8")
+}
+
+/// The encoding state shared by `EncodeWriter` and the async writer.
+pub(crate) struct EncodeState {
+    encoder: Encoder,
+    replace: bool,
+    /// An incomplete UTF-8 sequence from the end of the previous write.
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl EncodeState {
+    pub(crate) fn new(encoder: Encoder, replace: bool) -> EncodeState {
+        EncodeState {
+            encoder,
+            replace,
+            pending: [0; 4],
+            pending_len: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn encoder(&self) -> &Encoder {
+        &self.encoder
+    }
+
+    /// Encodes a prefix of the UTF-8 in `buf` into `output`, which must
+    /// have room for `IO_BUFFER_LENGTH` bytes. Returns the number of bytes
+    /// consumed and the number of bytes written.
+    ///
+    /// An error is returned only if nothing was consumed or written, as
+    /// `std::io::Write` expects.
+    pub(crate) fn encode(&mut self, buf: &[u8], output: &mut [u8]) -> io::Result<(usize, usize)> {
+        if self.pending_len != 0 {
+            // Complete the pending sequence first.
+            let needed = match self.pending[0] {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                _ => 4,
+            };
+            let take = core::cmp::min(needed - self.pending_len, buf.len());
+            let mut sequence = self.pending;
+            sequence[self.pending_len..self.pending_len + take].copy_from_slice(&buf[..take]);
+            let len = self.pending_len + take;
+            return match core::str::from_utf8(&sequence[..len]) {
+                Ok(string) => {
+                    self.pending_len = 0;
+                    match self.encode_str(string, output, false) {
+                        (None, _, written) => Ok((take, written)),
+                        (Some(c), _, _) => Err(self.unmappable(c)),
+                    }
+                }
+                Err(e) if e.error_len().is_none() => {
+                    self.pending = sequence;
+                    self.pending_len = len;
+                    Ok((take, 0))
+                }
+                Err(_) => Err(invalid_utf8()),
+            };
+        }
+        let (valid, error) = match core::str::from_utf8(buf) {
+            Ok(string) => (string, None),
+            Err(e) => {
+                // SAFETY: `valid_up_to()` is the length of valid UTF-8.
+                let valid = unsafe { core::str::from_utf8_unchecked(&buf[..e.valid_up_to()]) };
+                (valid, Some(e))
+            }
+        };
+        let (unmappable, read, written) = self.encode_str(valid, output, false);
+        if let Some(c) = unmappable {
+            // Report the part before the unmappable character as consumed
+            // so that the error is returned for a call that consumes
+            // nothing.
+            let before = read - c.len_utf8();
+            return if before > 0 || written > 0 {
+                Ok((before, written))
+            } else {
+                Err(self.unmappable(c))
+            };
+        }
+        if read < valid.len() {
+            return Ok((read, written));
+        }
+        match error {
+            None => Ok((read, written)),
+            Some(e) if e.error_len().is_none() => {
+                let tail = &buf[read..];
+                self.pending[..tail.len()].copy_from_slice(tail);
+                self.pending_len = tail.len();
+                Ok((buf.len(), written))
+            }
+            Some(_) if read > 0 => Ok((read, written)),
+            Some(_) => Err(invalid_utf8()),
+        }
+    }
+
+    /// Ends the stream writing the remaining output (the escape sequence
+    /// back to ASCII for ISO-2022-JP) into `output`.
+    pub(crate) fn finish(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        if self.pending_len != 0 {
+            return Err(invalid_utf8());
+        }
+        // Empty input can't contain an unmappable character.
+        let (_, _, written) = self.encode_str("", output, true);
+        Ok(written)
+    }
+
+    /// Encodes `src` into `output` with a single encoder call. Returns the
+    /// unmappable character, if any, and the number of bytes read and
+    /// written.
+    fn encode_str(
+        &mut self,
+        src: &str,
+        output: &mut [u8],
+        last: bool,
+    ) -> (Option<char>, usize, usize) {
+        if self.replace {
+            let (_, read, written, _) = self.encoder.encode_from_utf8(src, output, last);
+            (None, read, written)
+        } else {
+            let (result, read, written) = self
+                .encoder
+                .encode_from_utf8_without_replacement(src, output, last);
+            match result {
+                EncoderResult::Unmappable(c) => (Some(c), read, written),
+                _ => (None, read, written),
+            }
+        }
+    }
+
+    fn unmappable(&self, c: char) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            UnmappableError {
+                encoding: self.encoder.encoding(),
+                character: c,
+            },
+        )
+    }
+}
+
+/// Wraps a `std::io::Write` and encodes the UTF-8 written to it.
+///
+/// The encoding is that of the `Encoder` given to the constructor. The
+/// input is given as bytes via `std::io::Write` or as `str` via
+/// `write_str()`. The bytes must be UTF-8, but a UTF-8 sequence may be
+/// split across `write` calls. Invalid UTF-8 results in an error of the
+/// kind `std::io::ErrorKind::InvalidData`.
+///
+/// The encoded bytes are written to the wrapped writer on each `write`
+/// call. `finish()` must be called at the end of the stream for
+/// ISO-2022-JP to return to the ASCII state; dropping the writer without
+/// calling `finish()` may leave the output truncated.
+///
+/// # Example
+/// ```
+/// use encoding_rs::*;
+/// use std::io::Write;
+///
+/// let mut writer = EncodeWriter::new(Vec::new(), ISO_2022_JP.new_encoder());
+/// writer.write_all("日本".as_bytes()).unwrap();
+/// let bytes = writer.finish().unwrap();
+/// assert_eq!(bytes, b"\x1B$BF|K\\\x1B(B");
+/// ```
+pub struct EncodeWriter<W: Write> {
+    inner: W,
+    state: EncodeState,
+    output: Vec<u8>,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    /// Wraps `inner` so that unmappable characters are replaced with
+    /// HTML decimal numeric character references.
+    pub fn new(inner: W, encoder: Encoder) -> EncodeWriter<W> {
+        EncodeWriter {
+            inner,
+            state: EncodeState::new(encoder, true),
+            output: vec![0; IO_BUFFER_LENGTH],
+        }
+    }
+
+    /// Wraps `inner` so that an unmappable character results in an error of
+    /// the kind `std::io::ErrorKind::InvalidData` whose payload is an
+    /// `UnmappableError`.
+    pub fn new_without_replacement(inner: W, encoder: Encoder) -> EncodeWriter<W> {
+        EncodeWriter {
+            inner,
+            state: EncodeState::new(encoder, false),
+            output: vec![0; IO_BUFFER_LENGTH],
+        }
+    }
+
+    /// The encoding of the encoder.
+    #[inline]
+    pub fn encoding(&self) -> &'static Encoding {
+        self.state.encoder().encoding()
+    }
+
+    /// Returns a reference to the wrapped writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped writer.
+    ///
+    /// Writing to the wrapped writer directly can break the statefulness
+    /// of ISO-2022-JP.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Writes a string in its entirety.
+    pub fn write_str(&mut self, string: &str) -> io::Result<()> {
+        self.write_all(string.as_bytes())
+    }
+
+    /// Ends the stream, writes the remaining output (the escape sequence
+    /// back to ASCII for ISO-2022-JP), flushes and returns the wrapped
+    /// writer.
+    ///
+    /// Fails with `std::io::ErrorKind::InvalidData` if the input ended in
+    /// the middle of a UTF-8 sequence.
+    pub fn finish(mut self) -> io::Result<W> {
+        let written = self.state.finish(&mut self.output)?;
+        self.inner.write_all(&self.output[..written])?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let (read, written) = self.state.encode(buf, &mut self.output)?;
+            self.inner.write_all(&self.output[..written])?;
+            // Zero would mean failure to `write_all()`, so retry after
+            // output that didn't consume input.
+            if read != 0 {
+                return Ok(read);
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> core::fmt::Debug for EncodeWriter<W> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("EncodeWriter")
+            .field("encoder", &self.state.encoder)
+            .finish_non_exhaustive()
+    }
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use std::io::{BufRead, Read, Write};
+
+    /// Returns at most `chunk` bytes per call.
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.chunk.min(buf.len()).min(self.bytes.len());
+            buf[..len].copy_from_slice(&self.bytes[..len]);
+            self.bytes = &self.bytes[len..];
+            Ok(len)
+        }
+    }
+
+    fn read_all(reader: impl Read) -> String {
+        let mut reader = reader;
+        let mut string = String::new();
+        reader.read_to_string(&mut string).unwrap();
+        string
+    }
+
+    #[test]
+    fn test_decode_reader() {
+        let bytes = b"\xA4\xA2\xA4\xA4\xA4\xA6 EUC-JP";
+        for chunk in [1, 2, 3, 100] {
+            let reader = DecodeReader::new(Trickle { bytes, chunk }, EUC_JP.new_decoder());
+            assert_eq!(read_all(reader), "あいう EUC-JP");
+        }
+        let reader = DecodeReader::new(&b"\xA4\xA2\xA4"[..], EUC_JP.new_decoder());
+        assert_eq!(read_all(reader), "あ\u{FFFD}");
+        let reader = DecodeReader::new(&b""[..], EUC_JP.new_decoder());
+        assert_eq!(read_all(reader), "");
+    }
+
+    #[test]
+    fn test_decode_reader_bom() {
+        let mut reader = DecodeReader::new(&b"\xFF\xFEa\x00b\x00"[..], WINDOWS_1252.new_decoder());
+        let mut string = String::new();
+        reader.read_to_string(&mut string).unwrap();
+        assert_eq!(string, "ab");
+        assert_eq!(reader.encoding(), UTF_16LE);
+        let reader = DecodeReader::new(
+            &b"\xEF\xBB\xBFa"[..],
+            UTF_8.new_decoder_without_bom_handling(),
+        );
+        assert_eq!(read_all(reader), "\u{FEFF}a");
+    }
+
+    #[test]
+    fn test_decode_reader_without_replacement() {
+        let mut reader = DecodeReader::new_without_replacement(
+            &b"ab\xFFcd"[..],
+            UTF_8.new_decoder_without_bom_handling(),
+        );
+        let mut buf = [0u8; 16];
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"ab");
+        let err = reader.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"cd");
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        let mut reader = DecodeReader::new_without_replacement(
+            &b"ab\xE3\x81"[..],
+            UTF_8.new_decoder_without_bom_handling(),
+        );
+        let mut string = String::new();
+        assert!(reader.read_to_string(&mut string).is_err());
+    }
+
+    #[test]
+    fn test_decode_reader_buf_read() {
+        let bytes = "первая\nвторая\nтретья".to_string();
+        let (encoded, _, _) = KOI8_R.encode(&bytes);
+        let reader = DecodeReader::new(
+            Trickle {
+                bytes: &encoded,
+                chunk: 4,
+            },
+            KOI8_R.new_decoder(),
+        );
+        let lines: Vec<String> = reader.lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["первая", "вторая", "третья"]);
+    }
+
+    #[test]
+    fn test_decode_reader_long() {
+        let string = "aé€".repeat(10000);
+        let (encoded, _, _) = WINDOWS_1252.encode(&string);
+        let reader = DecodeReader::new(&encoded[..], WINDOWS_1252.new_decoder());
+        assert_eq!(read_all(reader), string);
+    }
+
+    #[test]
+    fn test_encode_writer() {
+        let mut writer = EncodeWriter::new(Vec::new(), SHIFT_JIS.new_encoder());
+        // Split UTF-8 sequences
+        for b in "あい".as_bytes() {
+            assert_eq!(writer.write(&[*b]).unwrap(), 1);
+        }
+        writer.write_str("u\u{1F4A9}").unwrap();
+        assert_eq!(writer.encoding(), SHIFT_JIS);
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes, b"\x82\xA0\x82\xA2u&#128169;");
+
+        let mut writer = EncodeWriter::new(Vec::new(), ISO_2022_JP.new_encoder());
+        let string = "日本語".repeat(2000);
+        writer.write_all(string.as_bytes()).unwrap();
+        let bytes = writer.finish().unwrap();
+        let (decoded, malformed) = ISO_2022_JP.decode_without_bom_handling(&bytes);
+        assert!(!malformed);
+        assert_eq!(decoded, string);
+        assert!(bytes.ends_with(b"\x1B(B"));
+    }
+
+    #[test]
+    fn test_encode_writer_invalid_utf8() {
+        let mut writer = EncodeWriter::new(Vec::new(), GBK.new_encoder());
+        assert_eq!(writer.write(b"ab\xFFcd").unwrap(), 2);
+        let err = writer.write(b"\xFFcd").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(writer.write(b"\xE4").unwrap(), 1);
+        assert_eq!(
+            writer.write(b"a").unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        let mut writer = EncodeWriter::new(Vec::new(), GBK.new_encoder());
+        writer.write_all(b"ab\xE4\xB8").unwrap();
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn test_encode_writer_without_replacement() {
+        let mut writer = EncodeWriter::new_without_replacement(Vec::new(), EUC_KR.new_encoder());
+        assert_eq!(writer.write("ab\u{3042}\u{00E9}c".as_bytes()).unwrap(), 5);
+        let err = writer.write("\u{00E9}c".as_bytes()).unwrap_err();
+        let unmappable = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<UnmappableError>()
+            .unwrap();
+        assert_eq!(unmappable.character(), '\u{00E9}');
+        assert_eq!(unmappable.encoding(), EUC_KR);
+        assert_eq!(err.to_string(), "U+00E9 cannot be encoded in EUC-KR");
+        assert_eq!(writer.write(b"c").unwrap(), 1);
+        assert_eq!(writer.finish().unwrap(), b"ab\xAA\xA2c");
+
+        let mut writer = EncodeWriter::new_without_replacement(Vec::new(), EUC_KR.new_encoder());
+        assert_eq!(writer.write(&"\u{00E9}".as_bytes()[..1]).unwrap(), 1);
+        assert!(writer.write(&"\u{00E9}".as_bytes()[1..]).is_err());
+    }
+}
@@ -498,6 +498,15 @@ impl Iso2022JpEncoder {
         checked_add(3, byte_length.checked_mul(3))
     }
 
+    pub fn can_encode(&self, c: char) -> bool {
+        match c {
+            '\u{0E}' | '\u{0F}' | '\u{1B}' => false,
+            '\u{0}'..='\u{7F}' | '\u{A5}' | '\u{203E}' => true,
+            '\u{80}'..='\u{FFFF}' => is_mapped_for_two_byte_encode(c as u16),
+            _ => false,
+        }
+    }
+
     encoder_functions!(
         eof = {
             match self.state {
@@ -76,10 +76,13 @@
 //!
 //! # Integration with `std::io`
 //!
-//! This crate doesn't implement traits from `std::io`. However, for the case of
-//! wrapping a `std::io::Read` in a decoder that implements `std::io::Read` and
-//! presents the data from the wrapped `std::io::Read` as UTF-8 is addressed by
-//! the [`encoding_rs_io`](https://docs.rs/encoding_rs_io/) crate.
+//! With the `std` feature enabled, [`DecodeReader`] wraps a `std::io::Read`
+//! in a decoder that implements `std::io::Read` and `std::io::BufRead` and
+//! presents the data from the wrapped `std::io::Read` as UTF-8, and
+//! [`EncodeWriter`] wraps a `std::io::Write` in an encoder that implements
+//! `std::io::Write` and accepts UTF-8. The
+//! [`encoding_rs_io`](https://docs.rs/encoding_rs_io/) crate provides similar
+//! decoding capability with more options.
 //!
 //! # Examples
 //!
@@ -279,9 +282,10 @@
 //! way will find encoding_rs useful. While encoding_rs does not try to match
 //! Windows behavior, many of the encodings are close enough to legacy
 //! encodings implemented by Windows that applications that need to consume
-//! data in legacy Windows encodins may find encoding_rs useful. The
-//! [codepage](https://crates.io/crates/codepage) crate maps from Windows
-//! code page identifiers onto encoding_rs `Encoding`s and vice versa.
+//! data in legacy Windows encodins may find encoding_rs useful.
+//! `Encoding::for_windows_code_page()` and `Encoding::windows_code_page()`
+//! map from Windows code page identifiers onto encoding_rs `Encoding`s and
+//! vice versa.
 //!
 //! For decoding email, UTF-7 support is needed (unfortunately) in additition
 //! to the encodings defined in the Encoding Standard. The
@@ -664,7 +668,7 @@
 //! <tr><td>ISO-8859-13</td><td>28603</td><td>&bullet;</td><td></td></tr>
 //! <tr><td>ISO-8859-15</td><td>28605</td><td></td><td></td></tr>
 //! <tr><td>ISO-8859-8-I</td><td>38598</td><td></td><td>5</td></tr>
-//! <tr><td>ISO-2022-JP</td><td>50220</td><td></td><td></td></tr>
+//! <tr><td>ISO-2022-JP</td><td>50221</td><td></td><td></td></tr>
 //! <tr><td>gb18030</td><td>54936</td><td></td><td></td></tr>
 //! <tr><td>UTF-8</td><td>65001</td><td></td><td></td></tr>
 //! </tbody>
@@ -685,6 +689,12 @@
 //! are not yet fully documented here. The lack of remarks above should not be taken
 //! as indication of lack of differences.
 //!
+//! The mapping in the table is available programmatically via
+//! `Encoding::windows_code_page()` and `Encoding::for_windows_code_page()`.
+//! The PUA column is available via
+//! `Encoding::windows_code_page_maps_unassigned_to_pua()` and the presence of
+//! remarks via `Encoding::windows_code_page_has_known_differences()`.
+//!
 //! # Notable Differences from IANA Naming
 //!
 //! In some cases, the Encoding Standard specifies the popular unextended encoding
@@ -720,6 +730,13 @@
 //!
 //! See the section [_UTF-16LE, UTF-16BE and Unicode Encoding Schemes_](#utf-16le-utf-16be-and-unicode-encoding-schemes)
 //! for discussion about the UTF-16 family.
+//!
+//! For protocols that identify character sets using IANA names or MIBenum
+//! numbers, `Encoding::iana_preferred_name()`, `Encoding::iana_mib()` and
+//! `Encoding::for_iana_mib()` map between the IANA registrations and the
+//! encodings. Since windows-949 and x-mac-ukrainian are not registered,
+//! `iana_preferred_name()` returns the names from the IANA column of the
+//! first table above only for Big5 and Shift_JIS.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(feature = "simd-accel", allow(internal_features))]
@@ -780,12 +797,41 @@ mod utf_8;
 mod x_user_defined;
 
 mod ascii;
+#[cfg(feature = "futures-io")]
+mod async_io;
 mod data;
+#[cfg(feature = "detect")]
+mod detector;
+mod encodable;
+mod fmt_write;
 mod handles;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "alloc")]
+mod lines;
+mod locale;
+mod mappings;
+mod mime;
+mod sniff;
 mod variant;
 
 pub mod mem;
 
+#[cfg(feature = "futures-io")]
+pub use crate::async_io::{AsyncDecodeReader, AsyncEncodeWriter};
+#[cfg(feature = "detect")]
+pub use crate::detector::EncodingDetector;
+#[cfg(feature = "alloc")]
+pub use crate::encodable::EncodableSet;
+pub use crate::encodable::UnmappableChars;
+pub use crate::fmt_write::EncodeFmtWriter;
+#[cfg(feature = "std")]
+pub use crate::io::{DecodeReader, EncodeWriter, UnmappableError};
+#[cfg(feature = "alloc")]
+pub use crate::lines::{LineBreak, LineDecoder, Lines};
+pub use crate::mappings::{Mapping, Mappings};
+pub use crate::sniff::{Confidence, SniffingDecoder, SniffingStep};
+
 use crate::ascii::ascii_valid_up_to;
 use crate::ascii::iso_2022_jp_ascii_valid_up_to;
 use crate::utf_8::utf8_valid_up_to;
@@ -821,6 +867,8 @@ const NCR_EXTRA: usize = 10; // &#1114111;
 
 const LONGEST_LABEL_LENGTH: usize = 19; // cseucpkdfmtjapanese
 
+const LONGEST_EXTENDED_LABEL_LENGTH: usize = 21; // unicodelittleunmarked
+
 /// The initializer for the [Big5](static.BIG5.html) encoding.
 ///
 /// For use only for taking the address of this form when
@@ -836,6 +884,7 @@ const LONGEST_LABEL_LENGTH: usize = 19; // cseucpkdfmtjapanese
 pub static BIG5_INIT: Encoding = Encoding {
     name: "Big5",
     variant: VariantEncoding::Big5,
+    id: EncodingId::Big5,
 };
 
 /// The Big5 encoding.
@@ -878,6 +927,7 @@ pub static BIG5: &'static Encoding = &BIG5_INIT;
 pub static EUC_JP_INIT: Encoding = Encoding {
     name: "EUC-JP",
     variant: VariantEncoding::EucJp,
+    id: EncodingId::EucJp,
 };
 
 /// The EUC-JP encoding.
@@ -918,6 +968,7 @@ pub static EUC_JP: &'static Encoding = &EUC_JP_INIT;
 pub static EUC_KR_INIT: Encoding = Encoding {
     name: "EUC-KR",
     variant: VariantEncoding::EucKr,
+    id: EncodingId::EucKr,
 };
 
 /// The EUC-KR encoding.
@@ -954,6 +1005,7 @@ pub static EUC_KR: &'static Encoding = &EUC_KR_INIT;
 pub static GBK_INIT: Encoding = Encoding {
     name: "GBK",
     variant: VariantEncoding::Gbk,
+    id: EncodingId::Gbk,
 };
 
 /// The GBK encoding.
@@ -997,6 +1049,7 @@ pub static GBK: &'static Encoding = &GBK_INIT;
 pub static IBM866_INIT: Encoding = Encoding {
     name: "IBM866",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.ibm866, 0x0440, 96, 16),
+    id: EncodingId::Ibm866,
 };
 
 /// The IBM866 encoding.
@@ -1031,6 +1084,7 @@ pub static IBM866: &'static Encoding = &IBM866_INIT;
 pub static ISO_2022_JP_INIT: Encoding = Encoding {
     name: "ISO-2022-JP",
     variant: VariantEncoding::Iso2022Jp,
+    id: EncodingId::Iso2022Jp,
 };
 
 /// The ISO-2022-JP encoding.
@@ -1067,6 +1121,7 @@ pub static ISO_2022_JP: &'static Encoding = &ISO_2022_JP_INIT;
 pub static ISO_8859_10_INIT: Encoding = Encoding {
     name: "ISO-8859-10",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_10, 0x00DA, 90, 6),
+    id: EncodingId::Iso8859_10,
 };
 
 /// The ISO-8859-10 encoding.
@@ -1101,6 +1156,7 @@ pub static ISO_8859_10: &'static Encoding = &ISO_8859_10_INIT;
 pub static ISO_8859_13_INIT: Encoding = Encoding {
     name: "ISO-8859-13",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_13, 0x00DF, 95, 1),
+    id: EncodingId::Iso8859_13,
 };
 
 /// The ISO-8859-13 encoding.
@@ -1135,6 +1191,7 @@ pub static ISO_8859_13: &'static Encoding = &ISO_8859_13_INIT;
 pub static ISO_8859_14_INIT: Encoding = Encoding {
     name: "ISO-8859-14",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_14, 0x00DF, 95, 17),
+    id: EncodingId::Iso8859_14,
 };
 
 /// The ISO-8859-14 encoding.
@@ -1169,6 +1226,7 @@ pub static ISO_8859_14: &'static Encoding = &ISO_8859_14_INIT;
 pub static ISO_8859_15_INIT: Encoding = Encoding {
     name: "ISO-8859-15",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_15, 0x00BF, 63, 65),
+    id: EncodingId::Iso8859_15,
 };
 
 /// The ISO-8859-15 encoding.
@@ -1202,6 +1260,7 @@ pub static ISO_8859_15: &'static Encoding = &ISO_8859_15_INIT;
 pub static ISO_8859_16_INIT: Encoding = Encoding {
     name: "ISO-8859-16",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_16, 0x00DF, 95, 4),
+    id: EncodingId::Iso8859_16,
 };
 
 /// The ISO-8859-16 encoding.
@@ -1236,6 +1295,7 @@ pub static ISO_8859_16: &'static Encoding = &ISO_8859_16_INIT;
 pub static ISO_8859_2_INIT: Encoding = Encoding {
     name: "ISO-8859-2",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_2, 0x00DF, 95, 1),
+    id: EncodingId::Iso8859_2,
 };
 
 /// The ISO-8859-2 encoding.
@@ -1268,6 +1328,7 @@ pub static ISO_8859_2: &'static Encoding = &ISO_8859_2_INIT;
 pub static ISO_8859_3_INIT: Encoding = Encoding {
     name: "ISO-8859-3",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_3, 0x00DF, 95, 4),
+    id: EncodingId::Iso8859_3,
 };
 
 /// The ISO-8859-3 encoding.
@@ -1300,6 +1361,7 @@ pub static ISO_8859_3: &'static Encoding = &ISO_8859_3_INIT;
 pub static ISO_8859_4_INIT: Encoding = Encoding {
     name: "ISO-8859-4",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_4, 0x00DF, 95, 1),
+    id: EncodingId::Iso8859_4,
 };
 
 /// The ISO-8859-4 encoding.
@@ -1332,6 +1394,7 @@ pub static ISO_8859_4: &'static Encoding = &ISO_8859_4_INIT;
 pub static ISO_8859_5_INIT: Encoding = Encoding {
     name: "ISO-8859-5",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_5, 0x040E, 46, 66),
+    id: EncodingId::Iso8859_5,
 };
 
 /// The ISO-8859-5 encoding.
@@ -1364,6 +1427,7 @@ pub static ISO_8859_5: &'static Encoding = &ISO_8859_5_INIT;
 pub static ISO_8859_6_INIT: Encoding = Encoding {
     name: "ISO-8859-6",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_6, 0x0621, 65, 26),
+    id: EncodingId::Iso8859_6,
 };
 
 /// The ISO-8859-6 encoding.
@@ -1397,6 +1461,7 @@ pub static ISO_8859_6: &'static Encoding = &ISO_8859_6_INIT;
 pub static ISO_8859_7_INIT: Encoding = Encoding {
     name: "ISO-8859-7",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_7, 0x03A3, 83, 44),
+    id: EncodingId::Iso8859_7,
 };
 
 /// The ISO-8859-7 encoding.
@@ -1434,6 +1499,7 @@ pub static ISO_8859_7: &'static Encoding = &ISO_8859_7_INIT;
 pub static ISO_8859_8_INIT: Encoding = Encoding {
     name: "ISO-8859-8",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_8, 0x05D0, 96, 27),
+    id: EncodingId::Iso8859_8,
 };
 
 /// The ISO-8859-8 encoding.
@@ -1469,6 +1535,7 @@ pub static ISO_8859_8: &'static Encoding = &ISO_8859_8_INIT;
 pub static ISO_8859_8_I_INIT: Encoding = Encoding {
     name: "ISO-8859-8-I",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.iso_8859_8, 0x05D0, 96, 27),
+    id: EncodingId::Iso8859_8I,
 };
 
 /// The ISO-8859-8-I encoding.
@@ -1504,6 +1571,7 @@ pub static ISO_8859_8_I: &'static Encoding = &ISO_8859_8_I_INIT;
 pub static KOI8_R_INIT: Encoding = Encoding {
     name: "KOI8-R",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.koi8_r, 0x044E, 64, 1),
+    id: EncodingId::Koi8R,
 };
 
 /// The KOI8-R encoding.
@@ -1536,6 +1604,7 @@ pub static KOI8_R: &'static Encoding = &KOI8_R_INIT;
 pub static KOI8_U_INIT: Encoding = Encoding {
     name: "KOI8-U",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.koi8_u, 0x044E, 64, 1),
+    id: EncodingId::Koi8U,
 };
 
 /// The KOI8-U encoding.
@@ -1568,6 +1637,7 @@ pub static KOI8_U: &'static Encoding = &KOI8_U_INIT;
 pub static SHIFT_JIS_INIT: Encoding = Encoding {
     name: "Shift_JIS",
     variant: VariantEncoding::ShiftJis,
+    id: EncodingId::ShiftJis,
 };
 
 /// The Shift_JIS encoding.
@@ -1602,6 +1672,7 @@ pub static SHIFT_JIS: &'static Encoding = &SHIFT_JIS_INIT;
 pub static UTF_16BE_INIT: Encoding = Encoding {
     name: "UTF-16BE",
     variant: VariantEncoding::Utf16Be,
+    id: EncodingId::Utf16Be,
 };
 
 /// The UTF-16BE encoding.
@@ -1636,6 +1707,7 @@ pub static UTF_16BE: &'static Encoding = &UTF_16BE_INIT;
 pub static UTF_16LE_INIT: Encoding = Encoding {
     name: "UTF-16LE",
     variant: VariantEncoding::Utf16Le,
+    id: EncodingId::Utf16Le,
 };
 
 /// The UTF-16LE encoding.
@@ -1670,6 +1742,7 @@ pub static UTF_16LE: &'static Encoding = &UTF_16LE_INIT;
 pub static UTF_8_INIT: Encoding = Encoding {
     name: "UTF-8",
     variant: VariantEncoding::Utf8,
+    id: EncodingId::Utf8,
 };
 
 /// The UTF-8 encoding.
@@ -1701,6 +1774,7 @@ pub static UTF_8: &'static Encoding = &UTF_8_INIT;
 pub static GB18030_INIT: Encoding = Encoding {
     name: "gb18030",
     variant: VariantEncoding::Gb18030,
+    id: EncodingId::Gb18030,
 };
 
 /// The gb18030 encoding.
@@ -1737,6 +1811,7 @@ pub static GB18030: &'static Encoding = &GB18030_INIT;
 pub static MACINTOSH_INIT: Encoding = Encoding {
     name: "macintosh",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.macintosh, 0x00CD, 106, 3),
+    id: EncodingId::Macintosh,
 };
 
 /// The macintosh encoding.
@@ -1770,6 +1845,7 @@ pub static MACINTOSH: &'static Encoding = &MACINTOSH_INIT;
 pub static REPLACEMENT_INIT: Encoding = Encoding {
     name: "replacement",
     variant: VariantEncoding::Replacement,
+    id: EncodingId::Replacement,
 };
 
 /// The replacement encoding.
@@ -1806,6 +1882,7 @@ pub static REPLACEMENT: &'static Encoding = &REPLACEMENT_INIT;
 pub static WINDOWS_1250_INIT: Encoding = Encoding {
     name: "windows-1250",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1250, 0x00DC, 92, 2),
+    id: EncodingId::Windows1250,
 };
 
 /// The windows-1250 encoding.
@@ -1838,6 +1915,7 @@ pub static WINDOWS_1250: &'static Encoding = &WINDOWS_1250_INIT;
 pub static WINDOWS_1251_INIT: Encoding = Encoding {
     name: "windows-1251",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1251, 0x0410, 64, 64),
+    id: EncodingId::Windows1251,
 };
 
 /// The windows-1251 encoding.
@@ -1870,6 +1948,7 @@ pub static WINDOWS_1251: &'static Encoding = &WINDOWS_1251_INIT;
 pub static WINDOWS_1252_INIT: Encoding = Encoding {
     name: "windows-1252",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1252, 0x00A0, 32, 96),
+    id: EncodingId::Windows1252,
 };
 
 /// The windows-1252 encoding.
@@ -1903,6 +1982,7 @@ pub static WINDOWS_1252: &'static Encoding = &WINDOWS_1252_INIT;
 pub static WINDOWS_1253_INIT: Encoding = Encoding {
     name: "windows-1253",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1253, 0x03A3, 83, 44),
+    id: EncodingId::Windows1253,
 };
 
 /// The windows-1253 encoding.
@@ -1937,6 +2017,7 @@ pub static WINDOWS_1253: &'static Encoding = &WINDOWS_1253_INIT;
 pub static WINDOWS_1254_INIT: Encoding = Encoding {
     name: "windows-1254",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1254, 0x00DF, 95, 17),
+    id: EncodingId::Windows1254,
 };
 
 /// The windows-1254 encoding.
@@ -1970,6 +2051,7 @@ pub static WINDOWS_1254: &'static Encoding = &WINDOWS_1254_INIT;
 pub static WINDOWS_1255_INIT: Encoding = Encoding {
     name: "windows-1255",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1255, 0x05D0, 96, 27),
+    id: EncodingId::Windows1255,
 };
 
 /// The windows-1255 encoding.
@@ -2004,6 +2086,7 @@ pub static WINDOWS_1255: &'static Encoding = &WINDOWS_1255_INIT;
 pub static WINDOWS_1256_INIT: Encoding = Encoding {
     name: "windows-1256",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1256, 0x0621, 65, 22),
+    id: EncodingId::Windows1256,
 };
 
 /// The windows-1256 encoding.
@@ -2036,6 +2119,7 @@ pub static WINDOWS_1256: &'static Encoding = &WINDOWS_1256_INIT;
 pub static WINDOWS_1257_INIT: Encoding = Encoding {
     name: "windows-1257",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1257, 0x00DF, 95, 1),
+    id: EncodingId::Windows1257,
 };
 
 /// The windows-1257 encoding.
@@ -2069,6 +2153,7 @@ pub static WINDOWS_1257: &'static Encoding = &WINDOWS_1257_INIT;
 pub static WINDOWS_1258_INIT: Encoding = Encoding {
     name: "windows-1258",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_1258, 0x00DF, 95, 4),
+    id: EncodingId::Windows1258,
 };
 
 /// The windows-1258 encoding.
@@ -2106,6 +2191,7 @@ pub static WINDOWS_1258: &'static Encoding = &WINDOWS_1258_INIT;
 pub static WINDOWS_874_INIT: Encoding = Encoding {
     name: "windows-874",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.windows_874, 0x0E01, 33, 58),
+    id: EncodingId::Windows874,
 };
 
 /// The windows-874 encoding.
@@ -2139,6 +2225,7 @@ pub static WINDOWS_874: &'static Encoding = &WINDOWS_874_INIT;
 pub static X_MAC_CYRILLIC_INIT: Encoding = Encoding {
     name: "x-mac-cyrillic",
     variant: VariantEncoding::SingleByte(&data::SINGLE_BYTE_DATA.x_mac_cyrillic, 0x0430, 96, 31),
+    id: EncodingId::XMacCyrillic,
 };
 
 /// The x-mac-cyrillic encoding.
@@ -2171,6 +2258,7 @@ pub static X_MAC_CYRILLIC: &'static Encoding = &X_MAC_CYRILLIC_INIT;
 pub static X_USER_DEFINED_INIT: Encoding = Encoding {
     name: "x-user-defined",
     variant: VariantEncoding::UserDefined,
+    id: EncodingId::XUserDefined,
 };
 
 /// The x-user-defined encoding.
@@ -2650,335 +2738,1795 @@ static ENCODINGS_IN_LABEL_SORT: [&'static Encoding; 228] = [
     &EUC_JP_INIT,
 ];
 
-// END GENERATED CODE
+static ENCODINGS_IN_NAME_SORT: [&'static Encoding; 40] = [
+    &BIG5_INIT,
+    &EUC_JP_INIT,
+    &EUC_KR_INIT,
+    &GBK_INIT,
+    &IBM866_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_8859_10_INIT,
+    &ISO_8859_13_INIT,
+    &ISO_8859_14_INIT,
+    &ISO_8859_15_INIT,
+    &ISO_8859_16_INIT,
+    &ISO_8859_2_INIT,
+    &ISO_8859_3_INIT,
+    &ISO_8859_4_INIT,
+    &ISO_8859_5_INIT,
+    &ISO_8859_6_INIT,
+    &ISO_8859_7_INIT,
+    &ISO_8859_8_INIT,
+    &ISO_8859_8_I_INIT,
+    &KOI8_R_INIT,
+    &KOI8_U_INIT,
+    &SHIFT_JIS_INIT,
+    &UTF_16BE_INIT,
+    &UTF_16LE_INIT,
+    &UTF_8_INIT,
+    &GB18030_INIT,
+    &MACINTOSH_INIT,
+    &REPLACEMENT_INIT,
+    &WINDOWS_1250_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1252_INIT,
+    &WINDOWS_1253_INIT,
+    &WINDOWS_1254_INIT,
+    &WINDOWS_1255_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1258_INIT,
+    &WINDOWS_874_INIT,
+    &X_MAC_CYRILLIC_INIT,
+    &X_USER_DEFINED_INIT,
+];
 
-/// An encoding as defined in the [Encoding Standard][1].
-///
-/// An _encoding_ defines a mapping from a `u8` sequence to a `char` sequence
-/// and, in most cases, vice versa. Each encoding has a name, an output
-/// encoding, and one or more labels.
-///
-/// _Labels_ are ASCII-case-insensitive strings that are used to identify an
-/// encoding in formats and protocols. The _name_ of the encoding is the
-/// preferred label in the case appropriate for returning from the
-/// [`characterSet`][2] property of the `Document` DOM interface.
-///
-/// The _output encoding_ is the encoding used for form submission and URL
-/// parsing on Web pages in the encoding. This is UTF-8 for the replacement,
-/// UTF-16LE and UTF-16BE encodings and the encoding itself for other
-/// encodings.
-///
-/// [1]: https://encoding.spec.whatwg.org/
-/// [2]: https://dom.spec.whatwg.org/#dom-document-characterset
-///
-/// # Streaming vs. Non-Streaming
-///
-/// When you have the entire input in a single buffer, you can use the
-/// methods [`decode()`][3], [`decode_with_bom_removal()`][4],
-/// [`decode_without_bom_handling()`][5],
-/// [`decode_without_bom_handling_and_without_replacement()`][6] and
-/// [`encode()`][7]. (These methods are available to Rust callers only and are
-/// not available in the C API.) Unlike the rest of the API available to Rust,
-/// these methods perform heap allocations. You should the `Decoder` and
-/// `Encoder` objects when your input is split into multiple buffers or when
-/// you want to control the allocation of the output buffers.
-///
-/// [3]: #method.decode
-/// [4]: #method.decode_with_bom_removal
-/// [5]: #method.decode_without_bom_handling
-/// [6]: #method.decode_without_bom_handling_and_without_replacement
-/// [7]: #method.encode
-///
-/// # Instances
-///
-/// All instances of `Encoding` are statically allocated and have the `'static`
-/// lifetime. There is precisely one unique `Encoding` instance for each
-/// encoding defined in the Encoding Standard.
-///
-/// To obtain a reference to a particular encoding whose identity you know at
-/// compile time, use a `static` that refers to encoding. There is a `static`
-/// for each encoding. The `static`s are named in all caps with hyphens
-/// replaced with underscores (and in C/C++ have `_ENCODING` appended to the
-/// name). For example, if you know at compile time that you will want to
-/// decode using the UTF-8 encoding, use the `UTF_8` `static` (`UTF_8_ENCODING`
-/// in C/C++).
-///
-/// Additionally, there are non-reference-typed forms ending with `_INIT` to
-/// work around the problem that `static`s of the type `&'static Encoding`
-/// cannot be used to initialize items of an array whose type is
-/// `[&'static Encoding; N]`.
-///
-/// If you don't know what encoding you need at compile time and need to
-/// dynamically get an encoding by label, use
-/// <code>Encoding::<a href="#method.for_label">for_label</a>(<var>label</var>)</code>.
-///
-/// Instances of `Encoding` can be compared with `==` (in both Rust and in
-/// C/C++).
-pub struct Encoding {
-    name: &'static str,
-    variant: VariantEncoding,
+static ENCODINGS_IN_ID_ORDER: [&'static Encoding; 40] = [
+    &BIG5_INIT,
+    &EUC_JP_INIT,
+    &EUC_KR_INIT,
+    &GBK_INIT,
+    &IBM866_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_8859_10_INIT,
+    &ISO_8859_13_INIT,
+    &ISO_8859_14_INIT,
+    &ISO_8859_15_INIT,
+    &ISO_8859_16_INIT,
+    &ISO_8859_2_INIT,
+    &ISO_8859_3_INIT,
+    &ISO_8859_4_INIT,
+    &ISO_8859_5_INIT,
+    &ISO_8859_6_INIT,
+    &ISO_8859_7_INIT,
+    &ISO_8859_8_INIT,
+    &ISO_8859_8_I_INIT,
+    &KOI8_R_INIT,
+    &KOI8_U_INIT,
+    &SHIFT_JIS_INIT,
+    &UTF_16BE_INIT,
+    &UTF_16LE_INIT,
+    &UTF_8_INIT,
+    &GB18030_INIT,
+    &MACINTOSH_INIT,
+    &REPLACEMENT_INIT,
+    &WINDOWS_1250_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1252_INIT,
+    &WINDOWS_1253_INIT,
+    &WINDOWS_1254_INIT,
+    &WINDOWS_1255_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1258_INIT,
+    &WINDOWS_874_INIT,
+    &X_MAC_CYRILLIC_INIT,
+    &X_USER_DEFINED_INIT,
+];
+
+static NAME_SORT_INDICES_IN_ID_ORDER: [u8; 40] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39,
+];
+
+/// Identifies an encoding as a value that can be used with `match`.
+///
+/// There is one variant per encoding. The `u8` discriminants are stable, so
+/// `EncodingId` can be stored in a single byte using `u8::from()` and
+/// restored using `EncodingId::try_from()`. The discriminants are not the
+/// positions in `Encoding::all()`, since a new encoding gets the next unused
+/// discriminant.
+///
+/// Obtained from `Encoding::id()`. The encoding can be obtained back using
+/// `EncodingId::encoding()`.
+///
+/// When serialized using Serde (with the `serde` feature enabled),
+/// `EncodingId` is represented as the name of the encoding in
+/// human-readable formats and as the `u8` discriminant in other formats.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum EncodingId {
+    /// The [Big5](static.BIG5.html) encoding.
+    Big5 = 0,
+    /// The [EUC-JP](static.EUC_JP.html) encoding.
+    EucJp = 1,
+    /// The [EUC-KR](static.EUC_KR.html) encoding.
+    EucKr = 2,
+    /// The [GBK](static.GBK.html) encoding.
+    Gbk = 3,
+    /// The [IBM866](static.IBM866.html) encoding.
+    Ibm866 = 4,
+    /// The [ISO-2022-JP](static.ISO_2022_JP.html) encoding.
+    Iso2022Jp = 5,
+    /// The [ISO-8859-10](static.ISO_8859_10.html) encoding.
+    Iso8859_10 = 6,
+    /// The [ISO-8859-13](static.ISO_8859_13.html) encoding.
+    Iso8859_13 = 7,
+    /// The [ISO-8859-14](static.ISO_8859_14.html) encoding.
+    Iso8859_14 = 8,
+    /// The [ISO-8859-15](static.ISO_8859_15.html) encoding.
+    Iso8859_15 = 9,
+    /// The [ISO-8859-16](static.ISO_8859_16.html) encoding.
+    Iso8859_16 = 10,
+    /// The [ISO-8859-2](static.ISO_8859_2.html) encoding.
+    Iso8859_2 = 11,
+    /// The [ISO-8859-3](static.ISO_8859_3.html) encoding.
+    Iso8859_3 = 12,
+    /// The [ISO-8859-4](static.ISO_8859_4.html) encoding.
+    Iso8859_4 = 13,
+    /// The [ISO-8859-5](static.ISO_8859_5.html) encoding.
+    Iso8859_5 = 14,
+    /// The [ISO-8859-6](static.ISO_8859_6.html) encoding.
+    Iso8859_6 = 15,
+    /// The [ISO-8859-7](static.ISO_8859_7.html) encoding.
+    Iso8859_7 = 16,
+    /// The [ISO-8859-8](static.ISO_8859_8.html) encoding.
+    Iso8859_8 = 17,
+    /// The [ISO-8859-8-I](static.ISO_8859_8_I.html) encoding.
+    Iso8859_8I = 18,
+    /// The [KOI8-R](static.KOI8_R.html) encoding.
+    Koi8R = 19,
+    /// The [KOI8-U](static.KOI8_U.html) encoding.
+    Koi8U = 20,
+    /// The [Shift_JIS](static.SHIFT_JIS.html) encoding.
+    ShiftJis = 21,
+    /// The [UTF-16BE](static.UTF_16BE.html) encoding.
+    Utf16Be = 22,
+    /// The [UTF-16LE](static.UTF_16LE.html) encoding.
+    Utf16Le = 23,
+    /// The [UTF-8](static.UTF_8.html) encoding.
+    Utf8 = 24,
+    /// The [gb18030](static.GB18030.html) encoding.
+    Gb18030 = 25,
+    /// The [macintosh](static.MACINTOSH.html) encoding.
+    Macintosh = 26,
+    /// The [replacement](static.REPLACEMENT.html) encoding.
+    Replacement = 27,
+    /// The [windows-1250](static.WINDOWS_1250.html) encoding.
+    Windows1250 = 28,
+    /// The [windows-1251](static.WINDOWS_1251.html) encoding.
+    Windows1251 = 29,
+    /// The [windows-1252](static.WINDOWS_1252.html) encoding.
+    Windows1252 = 30,
+    /// The [windows-1253](static.WINDOWS_1253.html) encoding.
+    Windows1253 = 31,
+    /// The [windows-1254](static.WINDOWS_1254.html) encoding.
+    Windows1254 = 32,
+    /// The [windows-1255](static.WINDOWS_1255.html) encoding.
+    Windows1255 = 33,
+    /// The [windows-1256](static.WINDOWS_1256.html) encoding.
+    Windows1256 = 34,
+    /// The [windows-1257](static.WINDOWS_1257.html) encoding.
+    Windows1257 = 35,
+    /// The [windows-1258](static.WINDOWS_1258.html) encoding.
+    Windows1258 = 36,
+    /// The [windows-874](static.WINDOWS_874.html) encoding.
+    Windows874 = 37,
+    /// The [x-mac-cyrillic](static.X_MAC_CYRILLIC.html) encoding.
+    XMacCyrillic = 38,
+    /// The [x-user-defined](static.X_USER_DEFINED.html) encoding.
+    XUserDefined = 39,
 }
 
-impl Encoding {
-    /// Implements the
-    /// [_get an encoding_](https://encoding.spec.whatwg.org/#concept-encoding-get)
-    /// algorithm.
-    ///
-    /// If, after ASCII-lowercasing and removing leading and trailing
-    /// whitespace, the argument matches a label defined in the Encoding
-    /// Standard, `Some(&'static Encoding)` representing the corresponding
-    /// encoding is returned. If there is no match, `None` is returned.
-    ///
-    /// This is the right method to use if the action upon the method returning
-    /// `None` is to use a fallback encoding (e.g. `WINDOWS_1252`) instead.
-    /// When the action upon the method returning `None` is not to proceed with
-    /// a fallback but to refuse processing, `for_label_no_replacement()` is more
-    /// appropriate.
-    ///
-    /// The argument is of type `&[u8]` instead of `&str` to save callers
-    /// that are extracting the label from a non-UTF-8 protocol the trouble
-    /// of conversion to UTF-8. (If you have a `&str`, just call `.as_bytes()`
-    /// on it.)
-    ///
-    /// Available via the C wrapper.
-    ///
-    /// # Example
-    /// ```
-    /// use encoding_rs::Encoding;
-    ///
-    /// assert_eq!(Some(encoding_rs::UTF_8), Encoding::for_label(b"utf-8"));
-    /// assert_eq!(Some(encoding_rs::UTF_8), Encoding::for_label(b"unicode11utf8"));
-    ///
-    /// assert_eq!(Some(encoding_rs::ISO_8859_2), Encoding::for_label(b"latin2"));
-    ///
-    /// assert_eq!(Some(encoding_rs::UTF_16BE), Encoding::for_label(b"utf-16be"));
-    ///
-    /// assert_eq!(None, Encoding::for_label(b"unrecognized label"));
-    /// ```
-    pub fn for_label(label: &[u8]) -> Option<&'static Encoding> {
-        let mut trimmed = [0u8; LONGEST_LABEL_LENGTH];
-        let mut trimmed_pos = 0usize;
-        let mut iter = label.iter();
-        // before
-        loop {
-            match iter.next() {
-                None => {
-                    return None;
-                }
-                Some(byte) => {
-                    // The characters used in labels are:
-                    // a-z (except q, but excluding it below seems excessive)
-                    // 0-9
-                    // . _ - :
-                    match *byte {
-                        0x09u8 | 0x0Au8 | 0x0Cu8 | 0x0Du8 | 0x20u8 => {
-                            continue;
-                        }
-                        b'A'..=b'Z' => {
-                            trimmed[trimmed_pos] = *byte + 0x20u8;
-                            trimmed_pos = 1usize;
-                            break;
-                        }
-                        b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b':' | b'.' => {
-                            trimmed[trimmed_pos] = *byte;
-                            trimmed_pos = 1usize;
-                            break;
-                        }
-                        _ => {
-                            return None;
-                        }
-                    }
-                }
-            }
-        }
-        // inside
-        loop {
-            match iter.next() {
-                None => {
-                    break;
-                }
-                Some(byte) => {
-                    match *byte {
-                        0x09u8 | 0x0Au8 | 0x0Cu8 | 0x0Du8 | 0x20u8 => {
-                            break;
-                        }
-                        b'A'..=b'Z' => {
-                            if trimmed_pos == LONGEST_LABEL_LENGTH {
-                                // There's no encoding with a label this long
-                                return None;
-                            }
-                            trimmed[trimmed_pos] = *byte + 0x20u8;
-                            trimmed_pos += 1usize;
-                            continue;
-                        }
-                        b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b':' | b'.' => {
-                            if trimmed_pos == LONGEST_LABEL_LENGTH {
-                                // There's no encoding with a label this long
-                                return None;
-                            }
-                            trimmed[trimmed_pos] = *byte;
-                            trimmed_pos += 1usize;
+static DISPLAY_TITLES_IN_NAME_SORT: [&'static str; 40] = [
+    "Chinese, Traditional (Big5)",
+    "Japanese (EUC-JP)",
+    "Korean (EUC-KR)",
+    "Chinese, Simplified (GBK)",
+    "Cyrillic (IBM-866)",
+    "Japanese (ISO-2022-JP)",
+    "Nordic (ISO-8859-10)",
+    "Baltic (ISO-8859-13)",
+    "Celtic (ISO-8859-14)",
+    "Western (ISO-8859-15)",
+    "Romanian (ISO-8859-16)",
+    "Central European (ISO-8859-2)",
+    "South European (ISO-8859-3)",
+    "Baltic (ISO-8859-4)",
+    "Cyrillic (ISO-8859-5)",
+    "Arabic (ISO-8859-6)",
+    "Greek (ISO-8859-7)",
+    "Hebrew, Visual (ISO-8859-8)",
+    "Hebrew (ISO-8859-8-I)",
+    "Cyrillic (KOI8-R)",
+    "Cyrillic/Ukrainian (KOI8-U)",
+    "Japanese (Shift_JIS)",
+    "Unicode (UTF-16BE)",
+    "Unicode (UTF-16LE)",
+    "Unicode (UTF-8)",
+    "Chinese, Simplified (gb18030)",
+    "Western (MacRoman)",
+    "Replacement",
+    "Central European (Windows-1250)",
+    "Cyrillic (Windows-1251)",
+    "Western (Windows-1252)",
+    "Greek (Windows-1253)",
+    "Turkish (Windows-1254)",
+    "Hebrew (Windows-1255)",
+    "Arabic (Windows-1256)",
+    "Baltic (Windows-1257)",
+    "Vietnamese (Windows-1258)",
+    "Thai (Windows-874)",
+    "Cyrillic (MacCyrillic)",
+    "User Defined (x-user-defined)",
+];
+
+static SCRIPT_GROUPS_IN_NAME_SORT: [ScriptGroup; 40] = [
+    ScriptGroup::ChineseTraditional,
+    ScriptGroup::Japanese,
+    ScriptGroup::Korean,
+    ScriptGroup::ChineseSimplified,
+    ScriptGroup::Cyrillic,
+    ScriptGroup::Japanese,
+    ScriptGroup::Western,
+    ScriptGroup::Baltic,
+    ScriptGroup::Western,
+    ScriptGroup::Western,
+    ScriptGroup::CentralEuropean,
+    ScriptGroup::CentralEuropean,
+    ScriptGroup::Western,
+    ScriptGroup::Baltic,
+    ScriptGroup::Cyrillic,
+    ScriptGroup::Arabic,
+    ScriptGroup::Greek,
+    ScriptGroup::Hebrew,
+    ScriptGroup::Hebrew,
+    ScriptGroup::Cyrillic,
+    ScriptGroup::Cyrillic,
+    ScriptGroup::Japanese,
+    ScriptGroup::Unicode,
+    ScriptGroup::Unicode,
+    ScriptGroup::Unicode,
+    ScriptGroup::ChineseSimplified,
+    ScriptGroup::Western,
+    ScriptGroup::Other,
+    ScriptGroup::CentralEuropean,
+    ScriptGroup::Cyrillic,
+    ScriptGroup::Western,
+    ScriptGroup::Greek,
+    ScriptGroup::Turkish,
+    ScriptGroup::Hebrew,
+    ScriptGroup::Arabic,
+    ScriptGroup::Baltic,
+    ScriptGroup::Vietnamese,
+    ScriptGroup::Thai,
+    ScriptGroup::Cyrillic,
+    ScriptGroup::Other,
+];
+
+static WINDOWS_CODE_PAGES_IN_NAME_SORT: [u16; 40] = [
+    950, 20932, 949, 936, 866, 50221, 28600, 28603, 28604, 28605, 28606, 28592, 28593, 28594,
+    28595, 28596, 28597, 28598, 38598, 20866, 21866, 932, 1201, 1200, 65001, 54936, 10000, 0, 1250,
+    1251, 1252, 1253, 1254, 1255, 1256, 1257, 1258, 874, 10017, 0,
+];
+
+static WINDOWS_CODE_PAGE_PUA_IN_NAME_SORT: [bool; 40] = [
+    false, false, false, false, false, false, false, true, false, false, false, false, false,
+    false, false, true, true, true, false, false, false, false, false, false, false, false, false,
+    false, false, false, false, true, false, true, false, true, false, true, false, false,
+];
+
+static WINDOWS_CODE_PAGE_DIFFERENCES_IN_NAME_SORT: [bool; 40] = [
+    false, false, false, false, false, false, false, false, false, false, false, false, false,
+    false, false, false, true, true, true, false, false, false, false, false, false, false, true,
+    false, false, false, false, false, false, false, false, false, false, false, true, false,
+];
+
+static WINDOWS_CODE_PAGES_SORTED: [u16; 53] = [
+    866, 874, 932, 936, 949, 950, 951, 1200, 1201, 1250, 1251, 1252, 1253, 1254, 1255, 1256, 1257,
+    1258, 10000, 10007, 10017, 20866, 20932, 20936, 20949, 21010, 21866, 28591, 28592, 28593,
+    28594, 28595, 28596, 28597, 28598, 28599, 28600, 28601, 28603, 28604, 28605, 28606, 38598,
+    50220, 50221, 50222, 50225, 50227, 51936, 51949, 52936, 54936, 65001,
+];
+
+static ENCODINGS_IN_WINDOWS_CODE_PAGE_SORT: [&'static Encoding; 53] = [
+    &IBM866_INIT,
+    &WINDOWS_874_INIT,
+    &SHIFT_JIS_INIT,
+    &GBK_INIT,
+    &EUC_KR_INIT,
+    &BIG5_INIT,
+    &BIG5_INIT,
+    &UTF_16LE_INIT,
+    &UTF_16BE_INIT,
+    &WINDOWS_1250_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1252_INIT,
+    &WINDOWS_1253_INIT,
+    &WINDOWS_1254_INIT,
+    &WINDOWS_1255_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1258_INIT,
+    &MACINTOSH_INIT,
+    &X_MAC_CYRILLIC_INIT,
+    &X_MAC_CYRILLIC_INIT,
+    &KOI8_R_INIT,
+    &EUC_JP_INIT,
+    &GBK_INIT,
+    &EUC_KR_INIT,
+    &UTF_16LE_INIT,
+    &KOI8_U_INIT,
+    &WINDOWS_1252_INIT,
+    &ISO_8859_2_INIT,
+    &ISO_8859_3_INIT,
+    &ISO_8859_4_INIT,
+    &ISO_8859_5_INIT,
+    &ISO_8859_6_INIT,
+    &ISO_8859_7_INIT,
+    &ISO_8859_8_INIT,
+    &WINDOWS_1254_INIT,
+    &ISO_8859_10_INIT,
+    &WINDOWS_874_INIT,
+    &ISO_8859_13_INIT,
+    &ISO_8859_14_INIT,
+    &ISO_8859_15_INIT,
+    &ISO_8859_16_INIT,
+    &ISO_8859_8_I_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_2022_JP_INIT,
+    &REPLACEMENT_INIT,
+    &REPLACEMENT_INIT,
+    &GBK_INIT,
+    &EUC_KR_INIT,
+    &REPLACEMENT_INIT,
+    &GB18030_INIT,
+    &UTF_8_INIT,
+];
+
+static IANA_MIBS_IN_NAME_SORT: [u16; 40] = [
+    2101, 18, 38, 113, 2086, 39, 13, 109, 110, 111, 112, 5, 6, 7, 8, 9, 10, 11, 85, 2084, 2088,
+    2024, 1013, 1014, 106, 114, 2027, 0, 2250, 2251, 2252, 2253, 2254, 2255, 2256, 2257, 2258,
+    2109, 0, 0,
+];
+
+static IANA_NAMES_IN_NAME_SORT: [&'static str; 40] = [
+    "Big5-HKSCS",
+    "EUC-JP",
+    "EUC-KR",
+    "GBK",
+    "IBM866",
+    "ISO-2022-JP",
+    "ISO-8859-10",
+    "ISO-8859-13",
+    "ISO-8859-14",
+    "ISO-8859-15",
+    "ISO-8859-16",
+    "ISO-8859-2",
+    "ISO-8859-3",
+    "ISO-8859-4",
+    "ISO-8859-5",
+    "ISO-8859-6",
+    "ISO-8859-7",
+    "ISO-8859-8",
+    "ISO-8859-8-I",
+    "KOI8-R",
+    "KOI8-U",
+    "Windows-31J",
+    "UTF-16BE",
+    "UTF-16LE",
+    "UTF-8",
+    "GB18030",
+    "macintosh",
+    "",
+    "windows-1250",
+    "windows-1251",
+    "windows-1252",
+    "windows-1253",
+    "windows-1254",
+    "windows-1255",
+    "windows-1256",
+    "windows-1257",
+    "windows-1258",
+    "windows-874",
+    "",
+    "",
+];
+
+static IANA_MIBS_SORTED: [u16; 54] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 17, 18, 36, 37, 38, 39, 81, 82, 84, 85, 104, 105, 106,
+    109, 110, 111, 112, 113, 114, 1000, 1013, 1014, 1015, 2024, 2025, 2026, 2027, 2084, 2085, 2086,
+    2088, 2101, 2109, 2250, 2251, 2252, 2253, 2254, 2255, 2256, 2257, 2258, 2259,
+];
+
+static ENCODINGS_IN_IANA_MIB_SORT: [&'static Encoding; 54] = [
+    &WINDOWS_1252_INIT,
+    &WINDOWS_1252_INIT,
+    &ISO_8859_2_INIT,
+    &ISO_8859_3_INIT,
+    &ISO_8859_4_INIT,
+    &ISO_8859_5_INIT,
+    &ISO_8859_6_INIT,
+    &ISO_8859_7_INIT,
+    &ISO_8859_8_INIT,
+    &WINDOWS_1254_INIT,
+    &ISO_8859_10_INIT,
+    &SHIFT_JIS_INIT,
+    &EUC_JP_INIT,
+    &EUC_KR_INIT,
+    &REPLACEMENT_INIT,
+    &EUC_KR_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_8859_6_INIT,
+    &ISO_8859_6_INIT,
+    &ISO_8859_8_INIT,
+    &ISO_8859_8_I_INIT,
+    &REPLACEMENT_INIT,
+    &REPLACEMENT_INIT,
+    &UTF_8_INIT,
+    &ISO_8859_13_INIT,
+    &ISO_8859_14_INIT,
+    &ISO_8859_15_INIT,
+    &ISO_8859_16_INIT,
+    &GBK_INIT,
+    &GB18030_INIT,
+    &UTF_16LE_INIT,
+    &UTF_16BE_INIT,
+    &UTF_16LE_INIT,
+    &UTF_16LE_INIT,
+    &SHIFT_JIS_INIT,
+    &GBK_INIT,
+    &BIG5_INIT,
+    &MACINTOSH_INIT,
+    &KOI8_R_INIT,
+    &REPLACEMENT_INIT,
+    &IBM866_INIT,
+    &KOI8_U_INIT,
+    &BIG5_INIT,
+    &WINDOWS_874_INIT,
+    &WINDOWS_1250_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1252_INIT,
+    &WINDOWS_1253_INIT,
+    &WINDOWS_1254_INIT,
+    &WINDOWS_1255_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1258_INIT,
+    &WINDOWS_874_INIT,
+];
+
+static EXTENDED_LABELS_SORTED: [&'static str; 111] = [
+    "al16utf16",
+    "al32utf8",
+    "ar8mswin1256",
+    "big5hkscs",
+    "big5solaris",
+    "blt8mswin1257",
+    "cl8koi8r",
+    "cl8koi8u",
+    "cl8mswin1251",
+    "cp65001",
+    "cp874",
+    "cp932",
+    "cp936",
+    "cp949",
+    "cp950",
+    "ee8iso8859p2",
+    "ee8mswin1250",
+    "el8mswin1253",
+    "euccn",
+    "eucjis2004",
+    "eucjisx0213",
+    "eucjp",
+    "eucjpms",
+    "eucjpopen",
+    "euckr",
+    "hz",
+    "hzgb",
+    "iso2022cn",
+    "iso2022cnext",
+    "iso2022jp",
+    "iso2022jp1",
+    "iso2022jp2",
+    "iso2022jp2004",
+    "iso2022jp3",
+    "iso2022jpext",
+    "iso2022kr",
+    "iw8mswin1255",
+    "ja16euc",
+    "ja16euctilde",
+    "ja16sjis",
+    "ja16sjistilde",
+    "ko16ksc5601",
+    "ko16mswin949",
+    "koi8u",
+    "l10",
+    "l7",
+    "l8",
+    "latin10",
+    "latin7",
+    "latin8",
+    "latin9",
+    "maccyrillic",
+    "macroman",
+    "ms874",
+    "ms936",
+    "ms949",
+    "ms950",
+    "ms950hkscs",
+    "mskanji",
+    "shiftjis",
+    "shiftjis2004",
+    "shiftjisx0213",
+    "sjis2004",
+    "sjisx0213",
+    "th8tisascii",
+    "tis620",
+    "tr8mswin1254",
+    "u8",
+    "ucs2be",
+    "ucs2le",
+    "uhc",
+    "ujis",
+    "unicodebigunmarked",
+    "unicodelittleunmarked",
+    "us7ascii",
+    "utf16",
+    "utf16be",
+    "utf16le",
+    "utf8mb3",
+    "utf8mb4",
+    "utf8sig",
+    "vn8mswin1258",
+    "we8iso8859p1",
+    "we8iso8859p15",
+    "we8mswin1252",
+    "win1250",
+    "win1251",
+    "win1252",
+    "win1253",
+    "win1254",
+    "win1255",
+    "win1256",
+    "win1257",
+    "win1258",
+    "win866",
+    "win874",
+    "windows9362000",
+    "xeucjpopen",
+    "xmaccyrillic",
+    "xmacroman",
+    "xmacukraine",
+    "xmswin936",
+    "xutf16lebom",
+    "xwindows874",
+    "xwindows949",
+    "xwindows950",
+    "zhs16gbk",
+    "zhs32gb18030",
+    "zht16big5",
+    "zht16hkscs",
+    "zht16mswin950",
+];
+
+static ENCODINGS_IN_EXTENDED_LABEL_SORT: [&'static Encoding; 111] = [
+    &UTF_16BE_INIT,
+    &UTF_8_INIT,
+    &WINDOWS_1256_INIT,
+    &BIG5_INIT,
+    &BIG5_INIT,
+    &WINDOWS_1257_INIT,
+    &KOI8_R_INIT,
+    &KOI8_U_INIT,
+    &WINDOWS_1251_INIT,
+    &UTF_8_INIT,
+    &WINDOWS_874_INIT,
+    &SHIFT_JIS_INIT,
+    &GBK_INIT,
+    &EUC_KR_INIT,
+    &BIG5_INIT,
+    &ISO_8859_2_INIT,
+    &WINDOWS_1250_INIT,
+    &WINDOWS_1253_INIT,
+    &GBK_INIT,
+    &EUC_JP_INIT,
+    &EUC_JP_INIT,
+    &EUC_JP_INIT,
+    &EUC_JP_INIT,
+    &EUC_JP_INIT,
+    &EUC_KR_INIT,
+    &REPLACEMENT_INIT,
+    &REPLACEMENT_INIT,
+    &REPLACEMENT_INIT,
+    &REPLACEMENT_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_2022_JP_INIT,
+    &ISO_2022_JP_INIT,
+    &REPLACEMENT_INIT,
+    &WINDOWS_1255_INIT,
+    &EUC_JP_INIT,
+    &EUC_JP_INIT,
+    &SHIFT_JIS_INIT,
+    &SHIFT_JIS_INIT,
+    &EUC_KR_INIT,
+    &EUC_KR_INIT,
+    &KOI8_U_INIT,
+    &ISO_8859_16_INIT,
+    &ISO_8859_13_INIT,
+    &ISO_8859_14_INIT,
+    &ISO_8859_16_INIT,
+    &ISO_8859_13_INIT,
+    &ISO_8859_14_INIT,
+    &ISO_8859_15_INIT,
+    &X_MAC_CYRILLIC_INIT,
+    &MACINTOSH_INIT,
+    &WINDOWS_874_INIT,
+    &GBK_INIT,
+    &EUC_KR_INIT,
+    &BIG5_INIT,
+    &BIG5_INIT,
+    &SHIFT_JIS_INIT,
+    &SHIFT_JIS_INIT,
+    &SHIFT_JIS_INIT,
+    &SHIFT_JIS_INIT,
+    &SHIFT_JIS_INIT,
+    &SHIFT_JIS_INIT,
+    &WINDOWS_874_INIT,
+    &WINDOWS_874_INIT,
+    &WINDOWS_1254_INIT,
+    &UTF_8_INIT,
+    &UTF_16BE_INIT,
+    &UTF_16LE_INIT,
+    &EUC_KR_INIT,
+    &EUC_JP_INIT,
+    &UTF_16BE_INIT,
+    &UTF_16LE_INIT,
+    &WINDOWS_1252_INIT,
+    &UTF_16LE_INIT,
+    &UTF_16BE_INIT,
+    &UTF_16LE_INIT,
+    &UTF_8_INIT,
+    &UTF_8_INIT,
+    &UTF_8_INIT,
+    &WINDOWS_1258_INIT,
+    &WINDOWS_1252_INIT,
+    &ISO_8859_15_INIT,
+    &WINDOWS_1252_INIT,
+    &WINDOWS_1250_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1252_INIT,
+    &WINDOWS_1253_INIT,
+    &WINDOWS_1254_INIT,
+    &WINDOWS_1255_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1258_INIT,
+    &IBM866_INIT,
+    &WINDOWS_874_INIT,
+    &GBK_INIT,
+    &EUC_JP_INIT,
+    &X_MAC_CYRILLIC_INIT,
+    &MACINTOSH_INIT,
+    &X_MAC_CYRILLIC_INIT,
+    &GBK_INIT,
+    &UTF_16LE_INIT,
+    &WINDOWS_874_INIT,
+    &EUC_KR_INIT,
+    &BIG5_INIT,
+    &GBK_INIT,
+    &GB18030_INIT,
+    &BIG5_INIT,
+    &BIG5_INIT,
+    &BIG5_INIT,
+];
+
+static EXTENDED_LABELS_EXACT: [bool; 111] = [
+    true, true, true, true, false, true, true, false, true, true, true, true, true, true, false,
+    true, true, true, true, false, false, true, false, false, true, false, false, false, false,
+    true, false, false, false, false, false, false, true, false, false, false, false, true, true,
+    true, true, true, true, true, true, true, true, true, true, true, true, true, false, false,
+    true, true, false, false, false, false, true, true, true, true, true, true, true, false, true,
+    true, true, true, true, true, true, true, true, true, false, true, true, true, true, true,
+    true, true, true, true, true, true, true, true, true, false, true, true, true, true, true,
+    true, true, false, true, true, false, false, false,
+];
+
+// END GENERATED CODE
+
+/// An encoding as defined in the [Encoding Standard][1].
+///
+/// An _encoding_ defines a mapping from a `u8` sequence to a `char` sequence
+/// and, in most cases, vice versa. Each encoding has a name, an output
+/// encoding, and one or more labels.
+///
+/// _Labels_ are ASCII-case-insensitive strings that are used to identify an
+/// encoding in formats and protocols. The _name_ of the encoding is the
+/// preferred label in the case appropriate for returning from the
+/// [`characterSet`][2] property of the `Document` DOM interface.
+///
+/// The _output encoding_ is the encoding used for form submission and URL
+/// parsing on Web pages in the encoding. This is UTF-8 for the replacement,
+/// UTF-16LE and UTF-16BE encodings and the encoding itself for other
+/// encodings.
+///
+/// [1]: https://encoding.spec.whatwg.org/
+/// [2]: https://dom.spec.whatwg.org/#dom-document-characterset
+///
+/// # Streaming vs. Non-Streaming
+///
+/// When you have the entire input in a single buffer, you can use the
+/// methods [`decode()`][3], [`decode_with_bom_removal()`][4],
+/// [`decode_without_bom_handling()`][5],
+/// [`decode_without_bom_handling_and_without_replacement()`][6] and
+/// [`encode()`][7]. (These methods are available to Rust callers only and are
+/// not available in the C API.) Unlike the rest of the API available to Rust,
+/// these methods perform heap allocations. You should the `Decoder` and
+/// `Encoder` objects when your input is split into multiple buffers or when
+/// you want to control the allocation of the output buffers.
+///
+/// [3]: #method.decode
+/// [4]: #method.decode_with_bom_removal
+/// [5]: #method.decode_without_bom_handling
+/// [6]: #method.decode_without_bom_handling_and_without_replacement
+/// [7]: #method.encode
+///
+/// # Instances
+///
+/// All instances of `Encoding` are statically allocated and have the `'static`
+/// lifetime. There is precisely one unique `Encoding` instance for each
+/// encoding defined in the Encoding Standard.
+///
+/// To obtain a reference to a particular encoding whose identity you know at
+/// compile time, use a `static` that refers to encoding. There is a `static`
+/// for each encoding. The `static`s are named in all caps with hyphens
+/// replaced with underscores (and in C/C++ have `_ENCODING` appended to the
+/// name). For example, if you know at compile time that you will want to
+/// decode using the UTF-8 encoding, use the `UTF_8` `static` (`UTF_8_ENCODING`
+/// in C/C++).
+///
+/// Additionally, there are non-reference-typed forms ending with `_INIT` to
+/// work around the problem that `static`s of the type `&'static Encoding`
+/// cannot be used to initialize items of an array whose type is
+/// `[&'static Encoding; N]`.
+///
+/// If you don't know what encoding you need at compile time and need to
+/// dynamically get an encoding by label, use
+/// <code>Encoding::<a href="#method.for_label">for_label</a>(<var>label</var>)</code>.
+///
+/// Instances of `Encoding` can be compared with `==` (in both Rust and in
+/// C/C++). In Rust, `Encoding::id()` returns an `EncodingId` that can be
+/// used with `match`.
+pub struct Encoding {
+    name: &'static str,
+    variant: VariantEncoding,
+    id: EncodingId,
+}
+
+impl Encoding {
+    /// Implements the
+    /// [_get an encoding_](https://encoding.spec.whatwg.org/#concept-encoding-get)
+    /// algorithm.
+    ///
+    /// If, after ASCII-lowercasing and removing leading and trailing
+    /// whitespace, the argument matches a label defined in the Encoding
+    /// Standard, `Some(&'static Encoding)` representing the corresponding
+    /// encoding is returned. If there is no match, `None` is returned.
+    ///
+    /// This is the right method to use if the action upon the method returning
+    /// `None` is to use a fallback encoding (e.g. `WINDOWS_1252`) instead.
+    /// When the action upon the method returning `None` is not to proceed with
+    /// a fallback but to refuse processing, `for_label_no_replacement()` is more
+    /// appropriate.
+    ///
+    /// The argument is of type `&[u8]` instead of `&str` to save callers
+    /// that are extracting the label from a non-UTF-8 protocol the trouble
+    /// of conversion to UTF-8. (If you have a `&str`, just call `.as_bytes()`
+    /// on it.)
+    ///
+    /// Available via the C wrapper.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::Encoding;
+    ///
+    /// assert_eq!(Some(encoding_rs::UTF_8), Encoding::for_label(b"utf-8"));
+    /// assert_eq!(Some(encoding_rs::UTF_8), Encoding::for_label(b"unicode11utf8"));
+    ///
+    /// assert_eq!(Some(encoding_rs::ISO_8859_2), Encoding::for_label(b"latin2"));
+    ///
+    /// assert_eq!(Some(encoding_rs::UTF_16BE), Encoding::for_label(b"utf-16be"));
+    ///
+    /// assert_eq!(None, Encoding::for_label(b"unrecognized label"));
+    /// ```
+    pub fn for_label(label: &[u8]) -> Option<&'static Encoding> {
+        let mut trimmed = [0u8; LONGEST_LABEL_LENGTH];
+        let mut trimmed_pos = 0usize;
+        let mut iter = label.iter();
+        // before
+        loop {
+            match iter.next() {
+                None => {
+                    return None;
+                }
+                Some(byte) => {
+                    // The characters used in labels are:
+                    // a-z (except q, but excluding it below seems excessive)
+                    // 0-9
+                    // . _ - :
+                    match *byte {
+                        0x09u8 | 0x0Au8 | 0x0Cu8 | 0x0Du8 | 0x20u8 => {
+                            continue;
+                        }
+                        b'A'..=b'Z' => {
+                            trimmed[trimmed_pos] = *byte + 0x20u8;
+                            trimmed_pos = 1usize;
+                            break;
+                        }
+                        b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b':' | b'.' => {
+                            trimmed[trimmed_pos] = *byte;
+                            trimmed_pos = 1usize;
+                            break;
+                        }
+                        _ => {
+                            return None;
+                        }
+                    }
+                }
+            }
+        }
+        // inside
+        loop {
+            match iter.next() {
+                None => {
+                    break;
+                }
+                Some(byte) => {
+                    match *byte {
+                        0x09u8 | 0x0Au8 | 0x0Cu8 | 0x0Du8 | 0x20u8 => {
+                            break;
+                        }
+                        b'A'..=b'Z' => {
+                            if trimmed_pos == LONGEST_LABEL_LENGTH {
+                                // There's no encoding with a label this long
+                                return None;
+                            }
+                            trimmed[trimmed_pos] = *byte + 0x20u8;
+                            trimmed_pos += 1usize;
+                            continue;
+                        }
+                        b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b':' | b'.' => {
+                            if trimmed_pos == LONGEST_LABEL_LENGTH {
+                                // There's no encoding with a label this long
+                                return None;
+                            }
+                            trimmed[trimmed_pos] = *byte;
+                            trimmed_pos += 1usize;
+                            continue;
+                        }
+                        _ => {
+                            return None;
+                        }
+                    }
+                }
+            }
+        }
+        // after
+        loop {
+            match iter.next() {
+                None => {
+                    break;
+                }
+                Some(byte) => {
+                    match *byte {
+                        0x09u8 | 0x0Au8 | 0x0Cu8 | 0x0Du8 | 0x20u8 => {
                             continue;
                         }
                         _ => {
+                            // There's no label with space in the middle
                             return None;
                         }
                     }
                 }
             }
         }
-        // after
-        loop {
-            match iter.next() {
-                None => {
-                    break;
-                }
-                Some(byte) => {
-                    match *byte {
-                        0x09u8 | 0x0Au8 | 0x0Cu8 | 0x0Du8 | 0x20u8 => {
-                            continue;
-                        }
-                        _ => {
-                            // There's no label with space in the middle
-                            return None;
-                        }
-                    }
-                }
-            }
+        let candidate = &trimmed[..trimmed_pos];
+        match LABELS_SORTED.binary_search_by(|probe| {
+            let bytes = probe.as_bytes();
+            let c = bytes.len().cmp(&candidate.len());
+            if c != Ordering::Equal {
+                return c;
+            }
+            let probe_iter = bytes.iter().rev();
+            let candidate_iter = candidate.iter().rev();
+            probe_iter.cmp(candidate_iter)
+        }) {
+            Ok(i) => Some(ENCODINGS_IN_LABEL_SORT[i]),
+            Err(_) => None,
+        }
+    }
+
+    /// This method behaves the same as `for_label()`, except when `for_label()`
+    /// would return `Some(REPLACEMENT)`, this method returns `None` instead.
+    ///
+    /// This method is useful in scenarios where a fatal error is required
+    /// upon invalid label, because in those cases the caller typically wishes
+    /// to treat the labels that map to the replacement encoding as fatal
+    /// errors, too.
+    ///
+    /// It is not OK to use this method when the action upon the method returning
+    /// `None` is to use a fallback encoding (e.g. `WINDOWS_1252`). In such a
+    /// case, the `for_label()` method should be used instead in order to avoid
+    /// unsafe fallback for labels that `for_label()` maps to `Some(REPLACEMENT)`.
+    ///
+    /// Available via the C wrapper.
+    #[inline]
+    pub fn for_label_no_replacement(label: &[u8]) -> Option<&'static Encoding> {
+        match Encoding::for_label(label) {
+            None => None,
+            Some(encoding) => {
+                if encoding == REPLACEMENT {
+                    None
+                } else {
+                    Some(encoding)
+                }
+            }
+        }
+    }
+
+    /// Like `for_label()` but additionally recognizes names that other
+    /// ecosystems (Java, Python, ICU, iconv and databases) use for character
+    /// encodings and maps them to the closest encoding.
+    ///
+    /// Returns `None` if there is no match. Otherwise, returns the encoding
+    /// and a `bool` indicating whether the match is exact. A match is exact
+    /// if the argument is a label defined in the Encoding Standard or a
+    /// spelling variant of one, because the Encoding Standard defines what
+    /// the label means (e.g. `latin1` means windows-1252), or if the
+    /// returned encoding decodes every byte sequence that is valid in the
+    /// named encoding to the same characters (e.g. `euc_cn` and `GBK`). For
+    /// example, `shift_jis_2004` maps to `SHIFT_JIS` but has characters that
+    /// `SHIFT_JIS` doesn't have, so the match is not exact. A match with
+    /// `REPLACEMENT` is never exact.
+    ///
+    /// If the argument is not a label defined in the Encoding Standard, it is
+    /// matched ignoring ASCII case and ignoring everything other than ASCII
+    /// letters and digits, so e.g. `ISO_8859-2`, `iso8859_2` and `ISO 8859 2`
+    /// are treated the same.
+    ///
+    /// This method is intended for interoperating with non-Web data. Do not
+    /// use it for processing Web content, where only the labels defined in
+    /// the Encoding Standard are supposed to be recognized.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::Encoding;
+    ///
+    /// assert_eq!(
+    ///     Encoding::for_label_extended(b"MS949"),
+    ///     Some((encoding_rs::EUC_KR, true))
+    /// );
+    /// assert_eq!(
+    ///     Encoding::for_label_extended(b"shift_jis_2004"),
+    ///     Some((encoding_rs::SHIFT_JIS, false))
+    /// );
+    /// assert_eq!(
+    ///     Encoding::for_label_extended(b"ISO8859_2"),
+    ///     Some((encoding_rs::ISO_8859_2, true))
+    /// );
+    /// assert_eq!(Encoding::for_label_extended(b"EBCDIC-US"), None);
+    /// ```
+    pub fn for_label_extended(label: &[u8]) -> Option<(&'static Encoding, bool)> {
+        if let Some(encoding) = Encoding::for_label(label) {
+            return Some((encoding, encoding != REPLACEMENT));
+        }
+        let mut normalized = [0u8; LONGEST_EXTENDED_LABEL_LENGTH];
+        let mut normalized_pos = 0usize;
+        for &byte in label {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => {
+                    if normalized_pos == LONGEST_EXTENDED_LABEL_LENGTH {
+                        // There's no encoding with a label this long
+                        return None;
+                    }
+                    normalized[normalized_pos] = byte.to_ascii_lowercase();
+                    normalized_pos += 1;
+                }
+                0x80..=0xFF => {
+                    return None;
+                }
+                _ => {}
+            }
+        }
+        let candidate = &normalized[..normalized_pos];
+        match EXTENDED_LABELS_SORTED.binary_search_by(|probe| probe.as_bytes().cmp(candidate)) {
+            Ok(i) => Some((
+                ENCODINGS_IN_EXTENDED_LABEL_SORT[i],
+                EXTENDED_LABELS_EXACT[i],
+            )),
+            Err(_) => {
+                Encoding::for_label(candidate).map(|encoding| (encoding, encoding != REPLACEMENT))
+            }
+        }
+    }
+
+    /// Returns the encoding for the codeset of a POSIX locale identifier of
+    /// the form `language[_territory][.codeset][@modifier]`, such as the
+    /// value of the `LANG` or `LC_ALL` environment variables.
+    ///
+    /// If the identifier includes a codeset, the codeset is resolved using
+    /// `for_label_extended()`, which understands the glibc spellings such as
+    /// `eucjp`, `utf8` and `ANSI_X3.4-1968`. If the codeset isn't recognized,
+    /// `None` is returned.
+    ///
+    /// If there is no codeset, the encoding of the codeset that glibc uses by
+    /// default for the locale is returned. For example, `ja_JP` yields
+    /// `EUC_JP`, `de_DE@euro` yields `ISO_8859_15` and `C` and `POSIX`
+    /// yield `WINDOWS_1252` (the Encoding Standard's encoding for ASCII).
+    ///
+    /// Returns `None` if the argument is not a locale identifier.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::Encoding;
+    ///
+    /// assert_eq!(Encoding::for_locale("ja_JP.eucJP"), Some(encoding_rs::EUC_JP));
+    /// assert_eq!(Encoding::for_locale("C.UTF-8"), Some(encoding_rs::UTF_8));
+    /// assert_eq!(Encoding::for_locale("de_DE@euro"), Some(encoding_rs::ISO_8859_15));
+    /// ```
+    pub fn for_locale(locale: &str) -> Option<&'static Encoding> {
+        locale::for_posix_locale(locale)
+    }
+
+    /// Returns the legacy encoding that Gecko falls back to for unlabeled
+    /// content when the user interface locale is the argument.
+    ///
+    /// The argument may be a BCP 47 language tag (e.g. `zh-TW`) or a POSIX
+    /// locale identifier (e.g. `zh_TW.UTF-8`). Only the language and, for
+    /// Chinese, the script and region matter. For example, `ja` yields
+    /// `SHIFT_JIS`, `zh-CN` yields `GBK`, `zh-TW` and `zh-Hant` yield `BIG5`
+    /// and `ru` yields `WINDOWS_1251`. Languages without a specific fallback
+    /// and arguments that aren't locale identifiers yield `WINDOWS_1252`.
+    ///
+    /// Use this for matching browser behavior when decoding unlabeled legacy
+    /// content and the top-level domain of the content is unknown or
+    /// `fallback_for_tld()` returns `WINDOWS_1252`.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(Encoding::fallback_for_locale("ja-JP"), SHIFT_JIS);
+    /// assert_eq!(Encoding::fallback_for_locale("en-US"), WINDOWS_1252);
+    /// ```
+    pub fn fallback_for_locale(locale: &str) -> &'static Encoding {
+        locale::fallback_for_locale(locale)
+    }
+
+    /// Returns the legacy encoding that Gecko falls back to for unlabeled
+    /// content from the top-level domain given as the argument.
+    ///
+    /// The argument is ASCII-case-insensitive and may be either just the
+    /// top-level domain (e.g. `jp`) or a host name (e.g. `example.co.jp`),
+    /// in which case the last label is used. Internationalized top-level
+    /// domains need to be given in their Punycode form (e.g. `xn--p1ai`).
+    /// Top-level domains without a specific fallback (including generic
+    /// top-level domains such as `com`) yield `WINDOWS_1252`.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(Encoding::fallback_for_tld(b"example.co.jp"), SHIFT_JIS);
+    /// assert_eq!(Encoding::fallback_for_tld(b"com"), WINDOWS_1252);
+    /// ```
+    pub fn fallback_for_tld(tld: &[u8]) -> &'static Encoding {
+        locale::fallback_for_tld(tld)
+    }
+
+    /// Performs non-incremental BOM sniffing.
+    ///
+    /// The argument must either be a buffer representing the entire input
+    /// stream (non-streaming case) or a buffer representing at least the first
+    /// three bytes of the input stream (streaming case).
+    ///
+    /// Returns `Some((UTF_8, 3))`, `Some((UTF_16LE, 2))` or
+    /// `Some((UTF_16BE, 2))` if the argument starts with the UTF-8, UTF-16LE
+    /// or UTF-16BE BOM or `None` otherwise.
+    ///
+    /// Available via the C wrapper.
+    #[inline]
+    pub fn for_bom(buffer: &[u8]) -> Option<(&'static Encoding, usize)> {
+        if buffer.starts_with(b"\xEF\xBB\xBF") {
+            Some((UTF_8, 3))
+        } else if buffer.starts_with(b"\xFF\xFE") {
+            Some((UTF_16LE, 2))
+        } else if buffer.starts_with(b"\xFE\xFF") {
+            Some((UTF_16BE, 2))
+        } else {
+            None
+        }
+    }
+
+    /// Runs the HTML Standard's algorithm for prescanning a byte stream to
+    /// determine its encoding.
+    ///
+    /// Looks at the first 1024 bytes of the argument (further bytes are
+    /// ignored) for a `meta` element that declares the encoding either with
+    /// the `charset` attribute or with `http-equiv="content-type"` and a
+    /// `content` attribute containing `charset=`. Labels are resolved using
+    /// `for_label()`. Comments and the attributes of other tags are skipped.
+    ///
+    /// As required by the spec, a declaration of UTF-16BE or UTF-16LE yields
+    /// `UTF_8` and a declaration of x-user-defined yields `WINDOWS_1252`.
+    ///
+    /// Returns `None` if no usable declaration is found, including when the
+    /// input ends in the middle of a tag.
+    ///
+    /// The BOM is not considered. Callers wishing to follow the HTML
+    /// Standard's encoding sniffing algorithm should check `for_bom()` first
+    /// and use a BOM-handling decoder.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(
+    ///     Encoding::prescan_html(b"<!DOCTYPE html><meta charset=\"Shift_JIS\">"),
+    ///     Some(SHIFT_JIS)
+    /// );
+    /// assert_eq!(Encoding::prescan_html(b"<p>Hello</p>"), None);
+    /// ```
+    pub fn prescan_html(bytes: &[u8]) -> Option<&'static Encoding> {
+        sniff::prescan_html(bytes)
+    }
+
+    /// Performs the autodetection of XML 1.0 Appendix F on the start of an
+    /// XML document.
+    ///
+    /// The argument should be a buffer containing at least the XML
+    /// declaration, if any.
+    ///
+    /// Returns the encoding and the length of the BOM, if any, as follows:
+    ///
+    /// * If the buffer starts with the UTF-8, UTF-16BE or UTF-16LE BOM, the
+    ///   encoding of the BOM and the length of the BOM.
+    /// * If the buffer starts with `<?` encoded as BOM-less UTF-16BE or
+    ///   UTF-16LE, `UTF_16BE` or `UTF_16LE` and zero.
+    /// * If the buffer starts with an XML declaration in an ASCII-compatible
+    ///   encoding and the declaration has an `encoding` pseudo-attribute
+    ///   whose value is recognized by `for_label()`, that encoding (`UTF_8`
+    ///   for a UTF-16 label, which can't be right) and zero.
+    ///
+    /// Returns `None` otherwise, including for UTF-32 and EBCDIC, which the
+    /// Encoding Standard doesn't support. In that case, XML documents
+    /// without external encoding information are UTF-8.
+    ///
+    /// Since the BOM is included in the returned length, the rest of the
+    /// buffer can be decoded with a decoder obtained from
+    /// `new_decoder_without_bom_handling()`.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(
+    ///     Encoding::sniff_xml(b"<?xml version=\"1.0\" encoding=\"EUC-JP\"?>"),
+    ///     Some((EUC_JP, 0))
+    /// );
+    /// assert_eq!(
+    ///     Encoding::sniff_xml(b"<\x00?\x00x\x00m\x00l\x00"),
+    ///     Some((UTF_16LE, 0))
+    /// );
+    /// assert_eq!(Encoding::sniff_xml(b"<root/>"), None);
+    /// ```
+    pub fn sniff_xml(bytes: &[u8]) -> Option<(&'static Encoding, usize)> {
+        sniff::sniff_xml(bytes)
+    }
+
+    /// Determines the encoding of a style sheet according to the CSS Syntax
+    /// specification.
+    ///
+    /// The first argument is the start of the style sheet. (Only the first
+    /// 1024 bytes are examined.) `protocol_charset` is the label given by the
+    /// `charset` parameter of the HTTP `Content-Type` header or equivalent,
+    /// if any, and `environment` is the encoding of the referring document
+    /// or the encoding given by the `charset` attribute of the referring
+    /// `link` element, if any.
+    ///
+    /// The encoding is chosen from the first of the following that applies:
+    ///
+    /// 1. The BOM.
+    /// 2. `protocol_charset`, if recognized by `for_label()`.
+    /// 3. An `@charset "label";` rule at the very start of the style sheet,
+    ///    matched byte for byte (case-sensitively, with exactly one space and
+    ///    double quotes), if the label is recognized by `for_label()`.
+    ///    UTF-16BE and UTF-16LE labels yield `UTF_8`, but labels of the
+    ///    replacement encoding yield `REPLACEMENT`.
+    /// 4. `environment`.
+    /// 5. `UTF_8`.
+    ///
+    /// The style sheet should then be decoded using a decoder obtained from
+    /// `new_decoder()` on the returned encoding, which removes the BOM.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(
+    ///     Encoding::for_css(b"@charset \"euc-kr\";", None, Some(SHIFT_JIS)),
+    ///     EUC_KR
+    /// );
+    /// assert_eq!(
+    ///     Encoding::for_css(b"@charset \"euc-kr\";", Some(b"big5"), None),
+    ///     BIG5
+    /// );
+    /// assert_eq!(Encoding::for_css(b"p { }", None, None), UTF_8);
+    /// ```
+    pub fn for_css(
+        bytes: &[u8],
+        protocol_charset: Option<&[u8]>,
+        environment: Option<&'static Encoding>,
+    ) -> &'static Encoding {
+        sniff::for_css(bytes, protocol_charset, environment)
+    }
+
+    /// Returns the encoding for the `charset` parameter of an HTTP or MIME
+    /// `Content-Type` header value.
+    ///
+    /// The header value is processed like the "extract a MIME type"
+    /// algorithm of the Fetch Standard does: The value is split on commas
+    /// outside quoted strings, each part is parsed according to the MIME
+    /// Sniffing Standard and the `charset` parameter of the last MIME type
+    /// that parsed (other than `*/*`) is used. If that MIME type doesn't have
+    /// a `charset` parameter but an earlier MIME type with the same essence
+    /// in an uninterrupted run of MIME types with that essence did, the
+    /// earlier parameter is used.
+    ///
+    /// Within a MIME type, parameter names are ASCII-case-insensitive,
+    /// quoted-string values are unescaped and only the first valid `charset`
+    /// parameter counts. The value is resolved using `for_label()`.
+    /// (`charset*` is a different parameter per the standard and doesn't
+    /// count.)
+    ///
+    /// Returns `None` if there is no usable `charset` parameter or the label
+    /// isn't recognized.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(
+    ///     Encoding::for_content_type(b"text/html; charset=\"Shift_JIS\""),
+    ///     Some(SHIFT_JIS)
+    /// );
+    /// assert_eq!(
+    ///     Encoding::for_content_type(b"text/html;charset=gbk, text/html"),
+    ///     Some(GBK)
+    /// );
+    /// assert_eq!(Encoding::for_content_type(b"text/html"), None);
+    /// ```
+    pub fn for_content_type(content_type: &[u8]) -> Option<&'static Encoding> {
+        mime::for_content_type(content_type)
+    }
+
+    /// Guesses whether BOM-less input is UTF-16LE or UTF-16BE.
+    ///
+    /// The guess is based on the positions of zero bytes: In UTF-16 text
+    /// with Latin-1 characters (including ASCII spaces and punctuation), the
+    /// high byte of most code units is zero. A guess is made only if at least
+    /// one in sixteen code units has a zero high byte for one byte order and
+    /// there are few such code units for the other byte order. Additionally,
+    /// the input must decode in the guessed byte order without unpaired
+    /// surrogates and without C0 control characters other than whitespace.
+    ///
+    /// The argument may be a prefix of the input. A trailing partial code
+    /// unit or surrogate pair is ignored.
+    ///
+    /// Returns `Some(UTF_16LE)`, `Some(UTF_16BE)` or `None` if the input
+    /// doesn't confidently look like UTF-16. In particular, UTF-16 text
+    /// consisting mostly of characters above U+00FF (e.g. CJK text) is not
+    /// recognized. The BOM is not considered; check `for_bom()` first.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(
+    ///     Encoding::guess_utf16_without_bom(b"H\x00e\x00l\x00l\x00o\x00"),
+    ///     Some(UTF_16LE)
+    /// );
+    /// assert_eq!(Encoding::guess_utf16_without_bom(b"Hello"), None);
+    /// ```
+    pub fn guess_utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+        utf_16::guess_without_bom(bytes)
+    }
+
+    /// Returns all the encodings defined in the Encoding Standard sorted by
+    /// name.
+    ///
+    /// Each encoding appears exactly once. This is useful for building
+    /// encoding menus and for exhaustive testing without hard-coding a list
+    /// that can go stale.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::Encoding;
+    ///
+    /// assert_eq!(Encoding::all().len(), 40);
+    /// assert!(Encoding::all().contains(&encoding_rs::SHIFT_JIS));
+    /// ```
+    #[inline]
+    pub fn all() -> &'static [&'static Encoding] {
+        &ENCODINGS_IN_NAME_SORT[..]
+    }
+
+    /// Returns an iterator over all the labels defined in the Encoding
+    /// Standard paired with the encoding each label maps to.
+    ///
+    /// The labels are in lower case and are yielded in the order
+    /// `for_label()` uses internally (sorted by length and then from the
+    /// end), which is not alphabetical. Every encoding has at least one
+    /// label. Note that labels for the replacement encoding are included.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::Encoding;
+    ///
+    /// for (label, encoding) in Encoding::labels() {
+    ///     assert_eq!(Encoding::for_label(label.as_bytes()), Some(encoding));
+    /// }
+    /// ```
+    #[inline]
+    pub fn labels() -> impl ExactSizeIterator<Item = (&'static str, &'static Encoding)> {
+        LABELS_SORTED
+            .iter()
+            .copied()
+            .zip(ENCODINGS_IN_LABEL_SORT.iter().copied())
+    }
+
+    /// Returns the name of this encoding.
+    ///
+    /// This name is appropriate to return as-is from the DOM
+    /// `document.characterSet` property.
+    ///
+    /// Available via the C wrapper.
+    #[inline]
+    pub fn name(&'static self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the `EncodingId` that identifies this encoding.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// match SHIFT_JIS.id() {
+    ///     EncodingId::ShiftJis => {}
+    ///     _ => unreachable!(),
+    /// }
+    /// assert_eq!(SHIFT_JIS.id().encoding(), SHIFT_JIS);
+    /// ```
+    #[inline]
+    pub fn id(&'static self) -> EncodingId {
+        self.id
+    }
+
+    /// Returns a descriptive English title for this encoding, such as
+    /// "Western (Windows-1252)", suitable for encoding menus and
+    /// diagnostics.
+    ///
+    /// The titles follow the titles that Firefox uses in its Text Encoding
+    /// menu. Except for the title of `REPLACEMENT`, which is "Replacement",
+    /// they end with a parenthesized form of the name of the encoding.
+    /// Applications that localize their user interface should localize the
+    /// titles themselves.
+    #[inline]
+    pub fn display_title(&'static self) -> &'static str {
+        DISPLAY_TITLES_IN_NAME_SORT[self.index_in_name_sort()]
+    }
+
+    /// Returns the script or region group for this encoding for grouping
+    /// encodings in encoding menus.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(SHIFT_JIS.script_group(), ScriptGroup::Japanese);
+    /// assert_eq!(UTF_8.script_group(), ScriptGroup::Unicode);
+    /// ```
+    #[inline]
+    pub fn script_group(&'static self) -> ScriptGroup {
+        SCRIPT_GROUPS_IN_NAME_SORT[self.index_in_name_sort()]
+    }
+
+    /// Checks whether the _output encoding_ of this encoding can encode every
+    /// `char`. (Only true if the output encoding is UTF-8.)
+    ///
+    /// Available via the C wrapper.
+    #[inline]
+    pub fn can_encode_everything(&'static self) -> bool {
+        self.output_encoding() == UTF_8
+    }
+
+    /// Checks whether the bytes 0x00...0x7F map exclusively to the characters
+    /// U+0000...U+007F and vice versa.
+    ///
+    /// Available via the C wrapper.
+    #[inline]
+    pub fn is_ascii_compatible(&'static self) -> bool {
+        !(self == REPLACEMENT || self == UTF_16BE || self == UTF_16LE || self == ISO_2022_JP)
+    }
+
+    /// Checks whether this encoding maps one byte to one Basic Multilingual
+    /// Plane code point (i.e. byte length equals decoded UTF-16 length) and
+    /// vice versa (for mappable characters).
+    ///
+    /// `true` iff this encoding is on the list of [Legacy single-byte
+    /// encodings](https://encoding.spec.whatwg.org/#legacy-single-byte-encodings)
+    /// in the spec or x-user-defined.
+    ///
+    /// Available via the C wrapper.
+    #[inline]
+    pub fn is_single_byte(&'static self) -> bool {
+        self.variant.is_single_byte()
+    }
+
+    /// Checks whether the bytes 0x00...0x7F map mostly to the characters
+    /// U+0000...U+007F and vice versa.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn is_potentially_borrowable(&'static self) -> bool {
+        !(self == REPLACEMENT || self == UTF_16BE || self == UTF_16LE)
+    }
+
+    /// Returns the _output encoding_ of this encoding. This is UTF-8 for
+    /// UTF-16BE, UTF-16LE, and replacement and the encoding itself otherwise.
+    ///
+    /// _Note:_ The _output encoding_ concept is needed for form submission and
+    /// error handling in the query strings of URLs in the Web Platform.
+    ///
+    /// Available via the C wrapper.
+    #[inline]
+    pub fn output_encoding(&'static self) -> &'static Encoding {
+        if self == REPLACEMENT || self == UTF_16BE || self == UTF_16LE {
+            UTF_8
+        } else {
+            self
         }
-        let candidate = &trimmed[..trimmed_pos];
-        match LABELS_SORTED.binary_search_by(|probe| {
-            let bytes = probe.as_bytes();
-            let c = bytes.len().cmp(&candidate.len());
-            if c != Ordering::Equal {
-                return c;
-            }
-            let probe_iter = bytes.iter().rev();
-            let candidate_iter = candidate.iter().rev();
-            probe_iter.cmp(candidate_iter)
-        }) {
-            Ok(i) => Some(ENCODINGS_IN_LABEL_SORT[i]),
+    }
+
+    /// Returns the position of this encoding in `all()`.
+    #[inline]
+    fn index_in_name_sort(&'static self) -> usize {
+        NAME_SORT_INDICES_IN_ID_ORDER[self.id as usize] as usize
+    }
+
+    /// Returns the encoding that is the closest match for a Windows code page
+    /// number or `None` if there is no closely related encoding.
+    ///
+    /// In addition to the code pages that `windows_code_page()` returns,
+    /// some related code pages are mapped to their closest encoding. For
+    /// example, 28591 (ISO-8859-1) maps to `WINDOWS_1252`, 50220 and 50222
+    /// map to `ISO_2022_JP` and code pages for encodings that the Encoding
+    /// Standard maps to the replacement encoding (e.g. 50225, ISO-2022-KR)
+    /// map to `REPLACEMENT`.
+    ///
+    /// See the section [_Relationship with Windows Code Pages_](index.html#relationship-with-windows-code-pages)
+    /// of the crate-level documentation for the differences between the
+    /// encodings and the code pages.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::Encoding;
+    ///
+    /// assert_eq!(Encoding::for_windows_code_page(932), Some(encoding_rs::SHIFT_JIS));
+    /// assert_eq!(Encoding::for_windows_code_page(28591), Some(encoding_rs::WINDOWS_1252));
+    /// assert_eq!(Encoding::for_windows_code_page(437), None);
+    /// ```
+    pub fn for_windows_code_page(code_page: u16) -> Option<&'static Encoding> {
+        match WINDOWS_CODE_PAGES_SORTED.binary_search(&code_page) {
+            Ok(i) => Some(ENCODINGS_IN_WINDOWS_CODE_PAGE_SORT[i]),
             Err(_) => None,
         }
     }
 
-    /// This method behaves the same as `for_label()`, except when `for_label()`
-    /// would return `Some(REPLACEMENT)`, this method returns `None` instead.
+    /// Returns the number of the Windows code page that is the closest match
+    /// for this encoding or `None` if there isn't one (replacement and
+    /// x-user-defined).
     ///
-    /// This method is useful in scenarios where a fatal error is required
-    /// upon invalid label, because in those cases the caller typically wishes
-    /// to treat the labels that map to the replacement encoding as fatal
-    /// errors, too.
+    /// `Encoding::for_windows_code_page()` maps the returned number back to
+    /// this encoding.
+    #[inline]
+    pub fn windows_code_page(&'static self) -> Option<u16> {
+        match WINDOWS_CODE_PAGES_IN_NAME_SORT[self.index_in_name_sort()] {
+            0 => None,
+            code_page => Some(code_page),
+        }
+    }
+
+    /// Checks whether the Windows code page returned by `windows_code_page()`
+    /// decodes byte values that are unassigned in this encoding to the
+    /// Private Use Area instead of U+FFFD.
     ///
-    /// It is not OK to use this method when the action upon the method returning
-    /// `None` is to use a fallback encoding (e.g. `WINDOWS_1252`). In such a
-    /// case, the `for_label()` method should be used instead in order to avoid
-    /// unsafe fallback for labels that `for_label()` maps to `Some(REPLACEMENT)`.
+    /// This is the PUA column of the table in the section
+    /// [_Relationship with Windows Code Pages_](index.html#relationship-with-windows-code-pages).
+    #[inline]
+    pub fn windows_code_page_maps_unassigned_to_pua(&'static self) -> bool {
+        WINDOWS_CODE_PAGE_PUA_IN_NAME_SORT[self.index_in_name_sort()]
+    }
+
+    /// Checks whether the Windows code page returned by `windows_code_page()`
+    /// is known to decode some byte values that are assigned in this encoding
+    /// to different characters.
     ///
-    /// Available via the C wrapper.
+    /// This corresponds to the numbered remarks in the section
+    /// [_Relationship with Windows Code Pages_](index.html#relationship-with-windows-code-pages).
+    /// The differences for multi-byte encodings have not been catalogued,
+    /// so `false` for a multi-byte encoding does not mean that there are
+    /// no differences.
     #[inline]
-    pub fn for_label_no_replacement(label: &[u8]) -> Option<&'static Encoding> {
-        match Encoding::for_label(label) {
-            None => None,
-            Some(encoding) => {
-                if encoding == REPLACEMENT {
-                    None
-                } else {
-                    Some(encoding)
-                }
-            }
+    pub fn windows_code_page_has_known_differences(&'static self) -> bool {
+        WINDOWS_CODE_PAGE_DIFFERENCES_IN_NAME_SORT[self.index_in_name_sort()]
+    }
+
+    /// Returns the encoding for an IANA character set MIBenum number or
+    /// `None` if the MIBenum does not identify an encoding that the Encoding
+    /// Standard supports.
+    ///
+    /// In addition to the MIBenums that `iana_mib()` returns, the MIBenums
+    /// of registrations that the Encoding Standard unifies into another
+    /// encoding are supported. For example, 4 (ISO-8859-1) maps to
+    /// `WINDOWS_1252`, 2026 (Big5) maps to `BIG5` and 37 (ISO-2022-KR) maps
+    /// to `REPLACEMENT`. In each case, the result is the same as calling
+    /// `for_label()` with the IANA name of the registration.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::Encoding;
+    ///
+    /// assert_eq!(Encoding::for_iana_mib(106), Some(encoding_rs::UTF_8));
+    /// assert_eq!(Encoding::for_iana_mib(4), Some(encoding_rs::WINDOWS_1252));
+    /// assert_eq!(Encoding::for_iana_mib(2), None);
+    /// ```
+    pub fn for_iana_mib(mib: u16) -> Option<&'static Encoding> {
+        match IANA_MIBS_SORTED.binary_search(&mib) {
+            Ok(i) => Some(ENCODINGS_IN_IANA_MIB_SORT[i]),
+            Err(_) => None,
         }
     }
 
-    /// Performs non-incremental BOM sniffing.
+    /// Returns the IANA character set MIBenum number of the IANA
+    /// registration that most precisely describes this encoding or `None` if
+    /// there isn't one (replacement, x-mac-cyrillic and x-user-defined).
     ///
-    /// The argument must either be a buffer representing the entire input
-    /// stream (non-streaming case) or a buffer representing at least the first
-    /// three bytes of the input stream (streaming case).
+    /// The registration is the one whose name `iana_preferred_name()`
+    /// returns, so e.g. `BIG5` yields 2101 (Big5-HKSCS) rather than 2026
+    /// (Big5).
+    #[inline]
+    pub fn iana_mib(&'static self) -> Option<u16> {
+        match IANA_MIBS_IN_NAME_SORT[self.index_in_name_sort()] {
+            0 => None,
+            mib => Some(mib),
+        }
+    }
+
+    /// Returns the IANA preferred MIME name (or the IANA name if there is no
+    /// preferred MIME name) of the IANA registration that most precisely
+    /// describes this encoding or `None` if there isn't one (replacement,
+    /// x-mac-cyrillic and x-user-defined).
     ///
-    /// Returns `Some((UTF_8, 3))`, `Some((UTF_16LE, 2))` or
-    /// `Some((UTF_16BE, 2))` if the argument starts with the UTF-8, UTF-16LE
-    /// or UTF-16BE BOM or `None` otherwise.
+    /// This differs from `name()` where the Encoding Standard uses the name
+    /// of the unextended encoding for an encoding that includes extensions.
+    /// See the section
+    /// [_Notable Differences from IANA Naming_](index.html#notable-differences-from-iana-naming)
+    /// of the crate-level documentation. Note that the IANA name for
+    /// `SHIFT_JIS` is Windows-31J and for `BIG5` it is Big5-HKSCS, which some
+    /// recipients may not recognize. `EUC_KR` yields EUC-KR, because
+    /// windows-949 is not registered.
     ///
-    /// Available via the C wrapper.
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(BIG5.iana_preferred_name(), Some("Big5-HKSCS"));
+    /// assert_eq!(WINDOWS_1252.iana_preferred_name(), Some("windows-1252"));
+    /// assert_eq!(X_USER_DEFINED.iana_preferred_name(), None);
+    /// ```
     #[inline]
-    pub fn for_bom(buffer: &[u8]) -> Option<(&'static Encoding, usize)> {
-        if buffer.starts_with(b"\xEF\xBB\xBF") {
-            Some((UTF_8, 3))
-        } else if buffer.starts_with(b"\xFF\xFE") {
-            Some((UTF_16LE, 2))
-        } else if buffer.starts_with(b"\xFE\xFF") {
-            Some((UTF_16BE, 2))
-        } else {
-            None
+    pub fn iana_preferred_name(&'static self) -> Option<&'static str> {
+        match IANA_NAMES_IN_NAME_SORT[self.index_in_name_sort()] {
+            "" => None,
+            name => Some(name),
         }
     }
 
-    /// Returns the name of this encoding.
+    /// Returns an iterator over the byte sequences that this encoding decodes
+    /// without errors paired with the characters that they decode to.
     ///
-    /// This name is appropriate to return as-is from the DOM
-    /// `document.characterSet` property.
+    /// Byte sequences are yielded in lexicographic order. Big5 has four byte
+    /// sequences that decode to two characters. The four-byte sequences of
+    /// gb18030 and GBK are included, but byte sequences that merely introduce
+    /// a state change are not. For ISO-2022-JP, the mappings of the ASCII
+    /// state are followed by those of the JIS X 0201 Roman, JIS X 0201
+    /// Katakana and JIS X 0208 states with each byte sequence prefixed by the
+    /// escape sequence that selects the state. For the UTF-8 and UTF-16
+    /// encodings the iterator yields every scalar value in order. The
+    /// replacement encoding yields nothing.
     ///
-    /// Available via the C wrapper.
-    #[inline]
-    pub fn name(&'static self) -> &'static str {
-        self.name
+    /// This is meant for building tables and test data. The mappings are
+    /// looked up in the index data without running the decoder, but gb18030
+    /// and GBK still yield over a million mappings, because the four-byte
+    /// sequences cover all of Unicode.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// let mapping = WINDOWS_1252.mappings().nth(0x80).unwrap();
+    /// assert_eq!(mapping.bytes(), b"\x80");
+    /// assert_eq!(mapping.chars(), ['\u{20AC}']);
+    /// ```
+    pub fn mappings(&'static self) -> Mappings {
+        Mappings::new(self)
     }
 
-    /// Checks whether the _output encoding_ of this encoding can encode every
-    /// `char`. (Only true if the output encoding is UTF-8.)
+    /// Checks whether the encoder for this encoding can encode `string`
+    /// without unmappable characters.
     ///
-    /// Available via the C wrapper.
-    #[inline]
-    pub fn can_encode_everything(&'static self) -> bool {
-        self.output_encoding() == UTF_8
+    /// This answers the question without producing output. As with
+    /// `encode()`, the output encoding is used, so UTF-16BE, UTF-16LE and
+    /// replacement can encode everything.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert!(WINDOWS_1252.can_encode("caf\u{E9}"));
+    /// assert!(!WINDOWS_1252.can_encode("\u{3042}"));
+    /// assert!(SHIFT_JIS.can_encode("\u{3042}"));
+    /// ```
+    pub fn can_encode(&'static self, string: &str) -> bool {
+        self.first_unmappable(string).is_none()
     }
 
-    /// Checks whether the bytes 0x00...0x7F map exclusively to the characters
-    /// U+0000...U+007F and vice versa.
+    /// Returns the byte offset and the value of the first character in
+    /// `string` that the encoder for this encoding cannot encode or `None`
+    /// if the encoder can encode the whole string.
     ///
-    /// Available via the C wrapper.
-    #[inline]
-    pub fn is_ascii_compatible(&'static self) -> bool {
-        !(self == REPLACEMENT || self == UTF_16BE || self == UTF_16LE || self == ISO_2022_JP)
+    /// In the case of ISO-2022-JP, the unmappable characters include
+    /// U+000E, U+000F and U+001B, which the encoder reports as U+FFFD. This
+    /// method reports the character that occurs in `string`.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// assert_eq!(EUC_KR.first_unmappable("a\u{AC00}\u{3042}\u{E4}"), Some((7, '\u{E4}')));
+    /// assert_eq!(EUC_KR.first_unmappable("a\u{AC00}"), None);
+    /// ```
+    pub fn first_unmappable(&'static self, string: &str) -> Option<(usize, char)> {
+        self.unmappable_chars(string).next()
     }
 
-    /// Checks whether this encoding maps one byte to one Basic Multilingual
-    /// Plane code point (i.e. byte length equals decoded UTF-16 length) and
-    /// vice versa (for mappable characters).
+    /// Returns an iterator over the characters in `string` that the encoder
+    /// for this encoding cannot encode paired with their byte offsets in
+    /// `string`.
     ///
-    /// `true` iff this encoding is on the list of [Legacy single-byte
-    /// encodings](https://encoding.spec.whatwg.org/#legacy-single-byte-encodings)
-    /// in the spec or x-user-defined.
+    /// The iterator skips over ASCII with the same fast path that the
+    /// encoders use and doesn't produce any output.
     ///
-    /// Available via the C wrapper.
-    #[inline]
-    pub fn is_single_byte(&'static self) -> bool {
-        self.variant.is_single_byte()
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// let mut iter = WINDOWS_1251.unmappable_chars("\u{E4}\u{0416}\u{3042}");
+    /// assert_eq!(iter.next(), Some((0, '\u{E4}')));
+    /// assert_eq!(iter.next(), Some((4, '\u{3042}')));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn unmappable_chars<'a>(&'static self, string: &'a str) -> UnmappableChars<'a> {
+        UnmappableChars::new(self, string)
     }
 
-    /// Checks whether the bytes 0x00...0x7F map mostly to the characters
-    /// U+0000...U+007F and vice versa.
+    /// Returns the set of Unicode scalar values that the encoder for this
+    /// encoding can encode.
+    ///
+    /// The set is computed from the same data that the encoder uses, so it
+    /// agrees with `can_encode()`. As with `encode()`, the output encoding is
+    /// used. Computing the set for a multi-byte encoding queries each BMP
+    /// character, so callers that need the set repeatedly should keep it.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// let gbk = GBK.encodable_set();
+    /// let gb18030 = GB18030.encodable_set();
+    /// assert!(gb18030.is_superset_of(&gbk));
+    /// assert!(gb18030.contains('\u{1F4A9}'));
+    /// assert!(!gbk.contains('\u{1F4A9}'));
+    /// ```
     #[cfg(feature = "alloc")]
-    #[inline]
-    fn is_potentially_borrowable(&'static self) -> bool {
-        !(self == REPLACEMENT || self == UTF_16BE || self == UTF_16LE)
+    pub fn encodable_set(&'static self) -> EncodableSet {
+        EncodableSet::new(self)
     }
 
-    /// Returns the _output encoding_ of this encoding. This is UTF-8 for
-    /// UTF-16BE, UTF-16LE, and replacement and the encoding itself otherwise.
+    /// Returns the output encoding of the first encoding in `candidates` that
+    /// can encode `string` without unmappable characters or UTF-8 if none
+    /// of them can.
     ///
-    /// _Note:_ The _output encoding_ concept is needed for form submission and
-    /// error handling in the query strings of URLs in the Web Platform.
+    /// The candidates are considered in order of preference, so e.g.
+    /// `[ISO_2022_JP, SHIFT_JIS, UTF_8]` expresses the traditional choice of
+    /// a Japanese mail client. The string isn't encoded; the check uses the
+    /// same machinery as `unmappable_chars()`. The output encoding is
+    /// returned, because UTF-16BE, UTF-16LE and replacement encode to UTF-8.
     ///
-    /// Available via the C wrapper.
-    #[inline]
-    pub fn output_encoding(&'static self) -> &'static Encoding {
-        if self == REPLACEMENT || self == UTF_16BE || self == UTF_16LE {
-            UTF_8
-        } else {
-            self
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// let candidates = [ISO_2022_JP, SHIFT_JIS, UTF_8];
+    /// assert_eq!(Encoding::best_for("\u{3042}", &candidates), ISO_2022_JP);
+    /// assert_eq!(Encoding::best_for("\u{FF61}", &candidates), ISO_2022_JP);
+    /// assert_eq!(Encoding::best_for("\u{80}", &candidates), SHIFT_JIS);
+    /// assert_eq!(Encoding::best_for("\u{1F600}", &candidates), UTF_8);
+    /// ```
+    pub fn best_for(string: &str, candidates: &[&'static Encoding]) -> &'static Encoding {
+        for candidate in candidates {
+            if candidate.can_encode(string) {
+                return candidate.output_encoding();
+            }
+        }
+        UTF_8
+    }
+
+    /// Like `best_for()` but returns the output encoding of the candidate
+    /// that encodes `string` into the fewest bytes. Among equally short
+    /// candidates, the one that comes first in `candidates` wins.
+    ///
+    /// Only the candidates that can encode `string` are encoded in order to
+    /// measure the length. The encoded bytes are not kept.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// let candidates = [ISO_2022_JP, SHIFT_JIS, UTF_8];
+    /// assert_eq!(Encoding::smallest_for("abc", &candidates), ISO_2022_JP);
+    /// assert_eq!(Encoding::smallest_for("\u{3042}", &candidates), SHIFT_JIS);
+    /// ```
+    pub fn smallest_for(string: &str, candidates: &[&'static Encoding]) -> &'static Encoding {
+        let mut best: Option<(&'static Encoding, usize)> = None;
+        for candidate in candidates {
+            if !candidate.can_encode(string) {
+                continue;
+            }
+            let length = crate::encodable::encoded_length(candidate, string);
+            match best {
+                Some((_, best_length)) if best_length <= length => {}
+                _ => {
+                    best = Some((candidate.output_encoding(), length));
+                }
+            }
         }
+        best.map_or(UTF_8, |(encoding, _)| encoding)
     }
 
     /// Decode complete input to `Cow<'a, str>` _with BOM sniffing_ and with
@@ -3544,6 +5092,138 @@ impl<'de> Deserialize<'de> for &'static Encoding {
     }
 }
 
+impl EncodingId {
+    /// Returns the encoding that this `EncodingId` identifies.
+    #[inline]
+    pub fn encoding(self) -> &'static Encoding {
+        ENCODINGS_IN_ID_ORDER[self as usize]
+    }
+}
+
+impl From<EncodingId> for u8 {
+    #[inline]
+    fn from(id: EncodingId) -> u8 {
+        id as u8
+    }
+}
+
+impl TryFrom<u8> for EncodingId {
+    /// The value that didn't identify an encoding.
+    type Error = u8;
+
+    #[inline]
+    fn try_from(value: u8) -> Result<EncodingId, u8> {
+        match ENCODINGS_IN_ID_ORDER.get(value as usize) {
+            Some(encoding) => Ok(encoding.id),
+            None => Err(value),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for EncodingId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(self.encoding().name)
+        } else {
+            serializer.serialize_u8(*self as u8)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for EncodingId {
+    fn deserialize<D>(deserializer: D) -> Result<EncodingId, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let encoding = deserializer.deserialize_str(EncodingVisitor)?;
+            Ok(encoding.id)
+        } else {
+            let value = u8::deserialize(deserializer)?;
+            EncodingId::try_from(value).map_err(|_| {
+                serde::de::Error::invalid_value(
+                    serde::de::Unexpected::Unsigned(u64::from(value)),
+                    &"a valid encoding id",
+                )
+            })
+        }
+    }
+}
+
+/// The script or region group of an encoding for grouping encodings in
+/// encoding menus.
+///
+/// Obtained from `Encoding::script_group()`. Encodings for Western European
+/// languages that aren't on the Western European code pages (such as
+/// ISO-8859-10 for Nordic languages) are grouped as `Western`, and
+/// ISO-8859-16 is grouped as `CentralEuropean`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ScriptGroup {
+    /// Arabic
+    Arabic,
+    /// Baltic
+    Baltic,
+    /// Central European
+    CentralEuropean,
+    /// Chinese, Simplified
+    ChineseSimplified,
+    /// Chinese, Traditional
+    ChineseTraditional,
+    /// Cyrillic
+    Cyrillic,
+    /// Greek
+    Greek,
+    /// Hebrew
+    Hebrew,
+    /// Japanese
+    Japanese,
+    /// Korean
+    Korean,
+    /// Thai
+    Thai,
+    /// Turkish
+    Turkish,
+    /// Unicode
+    Unicode,
+    /// Vietnamese
+    Vietnamese,
+    /// Western
+    Western,
+    /// The replacement encoding and x-user-defined, which aren't meant to
+    /// be offered in menus.
+    Other,
+}
+
+impl ScriptGroup {
+    /// Returns the English title of this group, such as "Central European",
+    /// suitable for labeling a submenu.
+    pub fn display_title(self) -> &'static str {
+        match self {
+            ScriptGroup::Arabic => "Arabic",
+            ScriptGroup::Baltic => "Baltic",
+            ScriptGroup::CentralEuropean => "Central European",
+            ScriptGroup::ChineseSimplified => "Chinese, Simplified",
+            ScriptGroup::ChineseTraditional => "Chinese, Traditional",
+            ScriptGroup::Cyrillic => "Cyrillic",
+            ScriptGroup::Greek => "Greek",
+            ScriptGroup::Hebrew => "Hebrew",
+            ScriptGroup::Japanese => "Japanese",
+            ScriptGroup::Korean => "Korean",
+            ScriptGroup::Thai => "Thai",
+            ScriptGroup::Turkish => "Turkish",
+            ScriptGroup::Unicode => "Unicode",
+            ScriptGroup::Vietnamese => "Vietnamese",
+            ScriptGroup::Western => "Western",
+            ScriptGroup::Other => "Other",
+        }
+    }
+}
+
 /// Tracks the life cycle of a decoder from BOM sniffing to conversion to end.
 #[derive(PartialEq, Debug, Copy, Clone)]
 enum DecoderLifeCycle {
@@ -4423,6 +6103,107 @@ impl Decoder {
             _ => None,
         }
     }
+
+    /// Switches this decoder to another encoding in the middle of the stream
+    /// and reports whether the bytes consumed so far decode identically in
+    /// both encodings.
+    ///
+    /// `consumed` must be all the bytes of the stream that this decoder has
+    /// consumed so far, excluding a BOM. After the call, this decoder is a
+    /// decoder for `encoding` (without BOM handling) in the state it would be
+    /// in had it decoded `consumed` using `encoding` from the start.
+    ///
+    /// Returns `true` if decoding `consumed` using `encoding` would have
+    /// produced the same output as decoding it using the current encoding.
+    /// In that case, the output produced so far remains valid and decoding
+    /// can continue without starting over. If `false` is returned, the
+    /// caller should discard the output and decode the stream from the start
+    /// using the new encoding.
+    ///
+    /// This is the check that the "change the encoding" algorithm of the
+    /// HTML Standard needs when a `meta` element is found late. (The UTF-16
+    /// and x-user-defined special cases of that algorithm are for the caller
+    /// to handle.) Runs in time linear to the length of `consumed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the decoder has finished.
+    ///
+    /// # Example
+    /// ```
+    /// use encoding_rs::*;
+    ///
+    /// let mut decoder = WINDOWS_1252.new_decoder_without_bom_handling();
+    /// let mut dst = [0u16; 64];
+    /// let consumed = b"<title>Hello</title><meta charset=gbk>";
+    /// let _ = decoder.decode_to_utf16(consumed, &mut dst, false);
+    /// assert!(decoder.switch_encoding(GBK, consumed));
+    /// assert_eq!(decoder.encoding(), GBK);
+    ///
+    /// let mut decoder = WINDOWS_1252.new_decoder_without_bom_handling();
+    /// let consumed = b"<title>\xC4\xE3\xBA\xC3</title><meta charset=gbk>";
+    /// let _ = decoder.decode_to_utf16(consumed, &mut dst, false);
+    /// assert!(!decoder.switch_encoding(GBK, consumed));
+    /// ```
+    pub fn switch_encoding(&mut self, encoding: &'static Encoding, consumed: &[u8]) -> bool {
+        if let DecoderLifeCycle::Finished = self.life_cycle {
+            panic!("Must not use a decoder that has finished.");
+        }
+        if encoding == self.encoding {
+            return true;
+        }
+        let mut old = self.encoding.new_decoder_without_bom_handling();
+        let mut new = encoding.new_decoder_without_bom_handling();
+        let same = same_utf16_output(&mut old, &mut new, consumed);
+        *self = new;
+        same
+    }
+}
+
+/// The number of input bytes `same_utf16_output()` decodes at a time.
+const SAME_OUTPUT_CHUNK_LENGTH: usize = 256;
+
+/// Feeds `bytes` to both decoders and checks whether the outputs are equal.
+/// The decoders may produce the same characters at different times, so the
+/// part of the output of the decoder that is ahead is carried over to the
+/// next round. `second` is fed all of `bytes` even after a difference has
+/// been found, so that it ends up in the state after `bytes`.
+fn same_utf16_output(first: &mut Decoder, second: &mut Decoder, bytes: &[u8]) -> bool {
+    let mut first_buffer = [0u16; 4 * SAME_OUTPUT_CHUNK_LENGTH];
+    let mut second_buffer = [0u16; 4 * SAME_OUTPUT_CHUNK_LENGTH];
+    let mut first_len = 0;
+    let mut second_len = 0;
+    let mut same = true;
+    for chunk in bytes.chunks(SAME_OUTPUT_CHUNK_LENGTH) {
+        let (result, _, written, _) =
+            second.decode_to_utf16(chunk, &mut second_buffer[second_len..], false);
+        debug_assert_eq!(result, CoderResult::InputEmpty);
+        second_len += written;
+        if !same {
+            second_len = 0;
+            continue;
+        }
+        let (result, _, written, _) =
+            first.decode_to_utf16(chunk, &mut first_buffer[first_len..], false);
+        debug_assert_eq!(result, CoderResult::InputEmpty);
+        first_len += written;
+        let common = core::cmp::min(first_len, second_len);
+        if first_buffer[..common] != second_buffer[..common] {
+            same = false;
+            second_len = 0;
+            continue;
+        }
+        first_buffer.copy_within(common..first_len, 0);
+        second_buffer.copy_within(common..second_len, 0);
+        first_len -= common;
+        second_len -= common;
+        // Decoders with the same output can't drift this far apart.
+        if first_len > SAME_OUTPUT_CHUNK_LENGTH || second_len > SAME_OUTPUT_CHUNK_LENGTH {
+            same = false;
+            second_len = 0;
+        }
+    }
+    same && first_len == 0 && second_len == 0
 }
 
 impl core::fmt::Debug for Decoder {
@@ -5470,6 +7251,246 @@ mod tests {
         assert_eq!(Encoding::for_label(b"bogusbogusbogusbogus"), None);
     }
 
+    #[test]
+    fn test_extended_label_resolution() {
+        for (label, encoding) in Encoding::labels() {
+            assert_eq!(
+                Encoding::for_label_extended(label.as_bytes()),
+                Some((encoding, encoding != REPLACEMENT))
+            );
+            // Spelling variants of labels, where recognized, are as exact as
+            // the labels.
+            let variant: String = label
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .collect();
+            if let Some(result) = Encoding::for_label_extended(variant.as_bytes()) {
+                assert_eq!(result, (encoding, encoding != REPLACEMENT));
+            }
+        }
+        for (i, label) in EXTENDED_LABELS_SORTED.iter().enumerate() {
+            assert_eq!(Encoding::for_label(label.as_bytes()), None);
+            assert_eq!(
+                Encoding::for_label_extended(label.as_bytes()),
+                Some((
+                    ENCODINGS_IN_EXTENDED_LABEL_SORT[i],
+                    EXTENDED_LABELS_EXACT[i]
+                ))
+            );
+        }
+        assert_eq!(
+            Encoding::for_label_extended(b" x-windows-949 "),
+            Some((EUC_KR, true))
+        );
+        assert_eq!(Encoding::for_label_extended(b"CP936"), Some((GBK, true)));
+        assert_eq!(
+            Encoding::for_label_extended(b"eucJP-ms"),
+            Some((EUC_JP, false))
+        );
+        assert_eq!(
+            Encoding::for_label_extended(b"latin_1"),
+            Some((WINDOWS_1252, true))
+        );
+        assert_eq!(
+            Encoding::for_label_extended(b"ISO_8859-1:1987"),
+            Some((WINDOWS_1252, true))
+        );
+        assert_eq!(
+            Encoding::for_label_extended(b"UTF-16-LE"),
+            Some((UTF_16LE, true))
+        );
+        assert_eq!(
+            Encoding::for_label_extended(b"UnicodeLittleUnmarked"),
+            Some((UTF_16LE, true))
+        );
+        assert_eq!(
+            Encoding::for_label_extended(b"unicodelittleunmarkedx"),
+            None
+        );
+        assert_eq!(
+            Encoding::for_label_extended(b"iso2022-kr"),
+            Some((REPLACEMENT, false))
+        );
+        assert_eq!(
+            Encoding::for_label_extended(b"utf_16"),
+            Some((UTF_16LE, true))
+        );
+        assert_eq!(Encoding::for_label_extended(b"euc_cn"), Some((GBK, true)));
+        assert_eq!(
+            Encoding::for_label_extended(b"hz"),
+            Some((REPLACEMENT, false))
+        );
+        assert_eq!(Encoding::for_label_extended(b""), None);
+        assert_eq!(Encoding::for_label_extended(b"-_-"), None);
+        assert_eq!(Encoding::for_label_extended(b"cp\xC3\xA9"), None);
+        assert_eq!(Encoding::for_label_extended(b"bogus"), None);
+    }
+
+    #[test]
+    fn test_all_encodings() {
+        let all = Encoding::all();
+        assert_eq!(all.len(), 40);
+        for (i, encoding) in all.iter().enumerate() {
+            assert_eq!(
+                Encoding::for_label(encoding.name().as_bytes()),
+                Some(*encoding)
+            );
+            assert!(!all[..i].contains(encoding));
+        }
+        assert_eq!(Encoding::labels().len(), 228);
+        for (_, encoding) in Encoding::labels() {
+            assert!(all.contains(&encoding));
+        }
+        for encoding in all {
+            assert!(Encoding::labels().any(|(_, e)| e == *encoding));
+        }
+    }
+
+    #[test]
+    fn test_encoding_id() {
+        for (i, encoding) in Encoding::all().iter().enumerate() {
+            let id = encoding.id();
+            assert_eq!(encoding.index_in_name_sort(), i);
+            assert_eq!(id.encoding(), *encoding);
+            assert_eq!(EncodingId::try_from(u8::from(id)), Ok(id));
+        }
+        assert_eq!(UTF_8.id(), EncodingId::Utf8);
+        assert_eq!(ISO_8859_8_I.id(), EncodingId::Iso8859_8I);
+        assert_eq!(EncodingId::try_from(40u8), Err(40u8));
+        assert_eq!(EncodingId::try_from(255u8), Err(255u8));
+    }
+
+    #[test]
+    fn test_encoding_id_values() {
+        // The discriminants are stable. Never change these, only add.
+        let ids = [
+            (EncodingId::Big5, 0),
+            (EncodingId::EucJp, 1),
+            (EncodingId::EucKr, 2),
+            (EncodingId::Gbk, 3),
+            (EncodingId::Ibm866, 4),
+            (EncodingId::Iso2022Jp, 5),
+            (EncodingId::Iso8859_10, 6),
+            (EncodingId::Iso8859_13, 7),
+            (EncodingId::Iso8859_14, 8),
+            (EncodingId::Iso8859_15, 9),
+            (EncodingId::Iso8859_16, 10),
+            (EncodingId::Iso8859_2, 11),
+            (EncodingId::Iso8859_3, 12),
+            (EncodingId::Iso8859_4, 13),
+            (EncodingId::Iso8859_5, 14),
+            (EncodingId::Iso8859_6, 15),
+            (EncodingId::Iso8859_7, 16),
+            (EncodingId::Iso8859_8, 17),
+            (EncodingId::Iso8859_8I, 18),
+            (EncodingId::Koi8R, 19),
+            (EncodingId::Koi8U, 20),
+            (EncodingId::ShiftJis, 21),
+            (EncodingId::Utf16Be, 22),
+            (EncodingId::Utf16Le, 23),
+            (EncodingId::Utf8, 24),
+            (EncodingId::Gb18030, 25),
+            (EncodingId::Macintosh, 26),
+            (EncodingId::Replacement, 27),
+            (EncodingId::Windows1250, 28),
+            (EncodingId::Windows1251, 29),
+            (EncodingId::Windows1252, 30),
+            (EncodingId::Windows1253, 31),
+            (EncodingId::Windows1254, 32),
+            (EncodingId::Windows1255, 33),
+            (EncodingId::Windows1256, 34),
+            (EncodingId::Windows1257, 35),
+            (EncodingId::Windows1258, 36),
+            (EncodingId::Windows874, 37),
+            (EncodingId::XMacCyrillic, 38),
+            (EncodingId::XUserDefined, 39),
+        ];
+        assert_eq!(ids.len(), Encoding::all().len());
+        for (id, value) in ids {
+            assert_eq!(u8::from(id), value);
+        }
+    }
+
+    #[test]
+    fn test_display_title_and_script_group() {
+        for encoding in Encoding::all() {
+            let title = encoding.display_title();
+            assert_eq!(title.ends_with(')'), *encoding != REPLACEMENT);
+            if encoding.script_group() != ScriptGroup::Other {
+                assert!(
+                    title.starts_with(encoding.script_group().display_title())
+                        || encoding.script_group() == ScriptGroup::Western
+                        || *encoding == ISO_8859_16
+                );
+            }
+        }
+        assert_eq!(WINDOWS_1252.display_title(), "Western (Windows-1252)");
+        assert_eq!(GBK.script_group(), ScriptGroup::ChineseSimplified);
+        assert_eq!(BIG5.script_group(), ScriptGroup::ChineseTraditional);
+        assert_eq!(KOI8_U.script_group(), ScriptGroup::Cyrillic);
+        assert_eq!(X_USER_DEFINED.script_group(), ScriptGroup::Other);
+    }
+
+    #[test]
+    fn test_windows_code_page() {
+        for encoding in Encoding::all() {
+            match encoding.windows_code_page() {
+                Some(code_page) => {
+                    assert_eq!(Encoding::for_windows_code_page(code_page), Some(*encoding));
+                }
+                None => {
+                    assert!(*encoding == REPLACEMENT || *encoding == X_USER_DEFINED);
+                    assert!(!encoding.windows_code_page_maps_unassigned_to_pua());
+                    assert!(!encoding.windows_code_page_has_known_differences());
+                }
+            }
+        }
+        assert_eq!(SHIFT_JIS.windows_code_page(), Some(932));
+        assert_eq!(UTF_8.windows_code_page(), Some(65001));
+        assert_eq!(Encoding::for_windows_code_page(1252), Some(WINDOWS_1252));
+        assert_eq!(Encoding::for_windows_code_page(28591), Some(WINDOWS_1252));
+        assert_eq!(Encoding::for_windows_code_page(50220), Some(ISO_2022_JP));
+        assert_eq!(Encoding::for_windows_code_page(50225), Some(REPLACEMENT));
+        assert_eq!(Encoding::for_windows_code_page(437), None);
+        assert_eq!(Encoding::for_windows_code_page(0), None);
+        assert!(WINDOWS_1253.windows_code_page_maps_unassigned_to_pua());
+        assert!(!WINDOWS_1252.windows_code_page_maps_unassigned_to_pua());
+        assert!(MACINTOSH.windows_code_page_has_known_differences());
+        assert!(ISO_8859_8_I.windows_code_page_has_known_differences());
+        assert!(!WINDOWS_1252.windows_code_page_has_known_differences());
+    }
+
+    #[test]
+    fn test_iana() {
+        for encoding in Encoding::all() {
+            match (encoding.iana_mib(), encoding.iana_preferred_name()) {
+                (Some(mib), Some(name)) => {
+                    assert_eq!(Encoding::for_iana_mib(mib), Some(*encoding));
+                    assert_eq!(Encoding::for_label(name.as_bytes()), Some(*encoding));
+                }
+                (None, None) => {
+                    assert!(
+                        *encoding == REPLACEMENT
+                            || *encoding == X_MAC_CYRILLIC
+                            || *encoding == X_USER_DEFINED
+                    );
+                }
+                _ => unreachable!(),
+            }
+        }
+        assert_eq!(BIG5.iana_mib(), Some(2101));
+        assert_eq!(SHIFT_JIS.iana_preferred_name(), Some("Windows-31J"));
+        assert_eq!(EUC_JP.iana_preferred_name(), Some("EUC-JP"));
+        assert_eq!(Encoding::for_iana_mib(2026), Some(BIG5));
+        assert_eq!(Encoding::for_iana_mib(17), Some(SHIFT_JIS));
+        assert_eq!(Encoding::for_iana_mib(3), Some(WINDOWS_1252));
+        assert_eq!(Encoding::for_iana_mib(12), Some(WINDOWS_1254));
+        assert_eq!(Encoding::for_iana_mib(1015), Some(UTF_16LE));
+        assert_eq!(Encoding::for_iana_mib(2085), Some(REPLACEMENT));
+        assert_eq!(Encoding::for_iana_mib(0), None);
+        assert_eq!(Encoding::for_iana_mib(1017), None);
+    }
+
     #[test]
     fn test_decode_valid_windows_1257_to_cow() {
         let (cow, encoding, had_errors) = WINDOWS_1257.decode(b"abc\x80\xE4");
@@ -6063,6 +8084,21 @@ mod tests {
         assert_eq!(debincoded, demo);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_encoding_id() {
+        let json = serde_json::to_string(&EncodingId::ShiftJis).unwrap();
+        assert_eq!(json, "\"Shift_JIS\"");
+        let id: EncodingId = serde_json::from_str(&json).unwrap();
+        assert_eq!(id, EncodingId::ShiftJis);
+
+        let bincoded = bincode::serialize(&EncodingId::ShiftJis).unwrap();
+        assert_eq!(bincoded, [EncodingId::ShiftJis as u8]);
+        let id: EncodingId = bincode::deserialize(&bincoded[..]).unwrap();
+        assert_eq!(id, EncodingId::ShiftJis);
+        assert!(bincode::deserialize::<EncodingId>(&[40u8][..]).is_err());
+    }
+
     #[test]
     fn test_is_single_byte() {
         assert!(!BIG5.is_single_byte());
@@ -6450,4 +8486,78 @@ mod tests {
             assert_eq!(written, 3);
         }
     }
+
+    #[test]
+    fn test_switch_encoding() {
+        let mut dst = [0u16; 2048];
+        let consumed = b"<p>Hello</p><meta charset=shift_jis>";
+        let mut decoder = UTF_8.new_decoder();
+        let _ = decoder.decode_to_utf16(consumed, &mut dst, false);
+        assert!(decoder.switch_encoding(SHIFT_JIS, consumed));
+        assert_eq!(decoder.encoding(), SHIFT_JIS);
+        let (result, read, written, _) = decoder.decode_to_utf16(b"\x82\xA0", &mut dst, true);
+        assert_eq!((result, read, written), (CoderResult::InputEmpty, 2, 1));
+        assert_eq!(dst[0], 0x3042);
+
+        // Same encoding
+        let mut decoder = GBK.new_decoder_without_bom_handling();
+        assert!(decoder.switch_encoding(GBK, b"\xC4\xE3"));
+
+        // Different interpretation
+        let mut decoder = WINDOWS_1252.new_decoder_without_bom_handling();
+        assert!(!decoder.switch_encoding(WINDOWS_1251, b"<p>\xE9</p>"));
+        assert_eq!(decoder.encoding(), WINDOWS_1251);
+
+        // Same interpretation of non-ASCII
+        let mut decoder = WINDOWS_1252.new_decoder_without_bom_handling();
+        assert!(decoder.switch_encoding(ISO_8859_15, b"<p>\xE9</p>"));
+        assert!(!decoder.switch_encoding(WINDOWS_1252, b"<p>\xA4</p>"));
+        let mut decoder = EUC_JP.new_decoder_without_bom_handling();
+        assert!(decoder.switch_encoding(ISO_2022_JP, b"abc"));
+        let mut decoder = ISO_2022_JP.new_decoder_without_bom_handling();
+        assert!(!decoder.switch_encoding(UTF_8, b"a\x1B$B$\"\x1B(Bb"));
+    }
+
+    #[test]
+    fn test_switch_encoding_pending() {
+        let mut dst = [0u16; 16];
+        // The new encoding is in the middle of a character.
+        let mut decoder = WINDOWS_1252.new_decoder_without_bom_handling();
+        assert!(!decoder.switch_encoding(UTF_8, b"ab\xC3"));
+        let (result, read, written, _) = decoder.decode_to_utf16(b"\xA9", &mut dst, true);
+        assert_eq!((result, read, written), (CoderResult::InputEmpty, 1, 1));
+        assert_eq!(dst[0], 0xE9);
+        // Both are in the middle of the same character.
+        let mut decoder = SHIFT_JIS.new_decoder_without_bom_handling();
+        assert!(decoder.switch_encoding(EUC_KR, b"ab\x82"));
+    }
+
+    #[test]
+    fn test_switch_encoding_long() {
+        let mut consumed = "<p>".repeat(400).into_bytes();
+        consumed.extend_from_slice(b"\xC3\xA9");
+        consumed.extend_from_slice("<p>".repeat(400).as_bytes());
+        let mut decoder = UTF_8.new_decoder_without_bom_handling();
+        assert!(!decoder.switch_encoding(WINDOWS_1252, &consumed));
+        let mut decoder = GBK.new_decoder_without_bom_handling();
+        assert!(decoder.switch_encoding(GB18030, &consumed));
+        // A character straddling the internal chunk boundary
+        let mut consumed = vec![b'a'; 255];
+        consumed.extend_from_slice(b"\x81\x40b");
+        let mut decoder = GBK.new_decoder_without_bom_handling();
+        assert!(decoder.switch_encoding(GB18030, &consumed));
+        let mut decoder = GBK.new_decoder_without_bom_handling();
+        assert!(!decoder.switch_encoding(BIG5, &consumed));
+        // The new decoder sees all of `consumed` even after the outputs
+        // differ in the first chunk.
+        let mut dst = [0u16; 16];
+        let mut consumed = b"\xC3\xA9".to_vec();
+        consumed.extend_from_slice(&[b'a'; 300]);
+        consumed.push(0x81);
+        let mut decoder = UTF_8.new_decoder_without_bom_handling();
+        assert!(!decoder.switch_encoding(GBK, &consumed));
+        let (result, read, written, _) = decoder.decode_to_utf16(b"\x40", &mut dst, true);
+        assert_eq!((result, read, written), (CoderResult::InputEmpty, 1, 1));
+        assert_eq!(dst[0], 0x4E02);
+    }
 }
@@ -0,0 +1,282 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+use alloc::string::String;
+
+/// Which character sequences end a line for `LineDecoder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineBreak {
+    /// LF ends a line. CR is part of the line.
+    Lf,
+    /// CR ends a line. LF is part of the line.
+    Cr,
+    /// CRLF ends a line. CR and LF on their own are part of the line.
+    CrLf,
+    /// CR, LF and CRLF each end a line.
+    Any,
+}
+
+/// Decodes a byte stream into lines.
+///
+/// Line breaks are found after decoding, so they are recognized correctly
+/// in encodings such as UTF-16 and ISO-2022-JP where the bytes 0x0A and
+/// 0x0D don't always mean LF and CR and the decoder state carries over from
+/// one line to the next. The line break characters are not included in the
+/// lines. Text after the last line break is returned as the last line at
+/// the end of the stream unless it is empty.
+///
+/// The input is given in buffers of any size via `decode()`, which returns
+/// an iterator over the lines completed so far. Lines not taken from the
+/// iterator are returned by the iterator returned by the next call.
+///
+/// # Example
+/// ```
+/// use encoding_rs::*;
+///
+/// let mut lines = LineDecoder::new(ISO_2022_JP.new_decoder(), LineBreak::Any);
+/// let mut result = Vec::new();
+/// for chunk in [&b"\x1B$B$3$s\x1B(B\n\x1B$B$K"[..], b"$A$O\x1B(B\r\nbye"] {
+///     result.extend(lines.decode(chunk, false));
+/// }
+/// result.extend(lines.decode(b"", true));
+/// assert_eq!(result, ["こん", "にちは", "bye"]);
+/// ```
+pub struct LineDecoder {
+    decoder: Decoder,
+    line_break: LineBreak,
+    text: String,
+    /// The start of the part of `text` not yet returned.
+    start: usize,
+    /// The position in `text` up to which the search for the next line
+    /// break has already been done, so that long lines are not searched
+    /// again after each buffer.
+    scanned: usize,
+    /// Whether a CR ended the previous line in `LineBreak::Any` mode, so
+    /// that an immediately following LF is not a line break of its own.
+    skip_lf: bool,
+    ended: bool,
+}
+
+impl LineDecoder {
+    /// Wraps a decoder for use with the given kind of line break.
+    pub fn new(decoder: Decoder, line_break: LineBreak) -> LineDecoder {
+        LineDecoder {
+            decoder,
+            line_break,
+            text: String::new(),
+            start: 0,
+            scanned: 0,
+            skip_lf: false,
+            ended: false,
+        }
+    }
+
+    /// The encoding of the decoder. BOM sniffing can change the return
+    /// value.
+    #[inline]
+    pub fn encoding(&self) -> &'static Encoding {
+        self.decoder.encoding()
+    }
+
+    /// Decodes `src` with malformed sequences replaced with the REPLACEMENT
+    /// CHARACTER and returns an iterator over the lines that are complete.
+    ///
+    /// `last` indicates the end of the stream as with `Decoder`. The
+    /// decoder must not be used after a call with `last` set to `true`.
+    pub fn decode(&mut self, src: &[u8], last: bool) -> Lines<'_> {
+        self.text.drain(..self.start);
+        self.scanned = self.scanned.saturating_sub(self.start);
+        self.start = 0;
+        let mut src = src;
+        loop {
+            let needed = self
+                .decoder
+                .max_utf8_buffer_length(src.len())
+                .unwrap_or(src.len());
+            self.text.reserve(needed);
+            let (result, read, _) = self.decoder.decode_to_string(src, &mut self.text, last);
+            src = &src[read..];
+            if result == CoderResult::InputEmpty {
+                break;
+            }
+        }
+        self.ended = last;
+        Lines { lines: self }
+    }
+
+    fn next_line(&mut self) -> Option<String> {
+        if self.skip_lf && self.start < self.text.len() {
+            self.skip_lf = false;
+            if self.text[self.start..].starts_with('\n') {
+                self.start += 1;
+            }
+        }
+        let from = self.scanned.max(self.start);
+        let rest = &self.text[from..];
+        let found = match self.line_break {
+            LineBreak::Lf => rest.find('\n').map(|i| (i, 1)),
+            LineBreak::Cr => rest.find('\r').map(|i| (i, 1)),
+            LineBreak::CrLf => rest.find("\r\n").map(|i| (i, 2)),
+            LineBreak::Any => rest.find(['\r', '\n']).map(|i| {
+                if rest.as_bytes()[i] == b'\r' {
+                    self.skip_lf = true;
+                }
+                (i, 1)
+            }),
+        };
+        match found {
+            Some((len, break_len)) => {
+                let end = from + len;
+                let line = String::from(&self.text[self.start..end]);
+                self.start = end + break_len;
+                self.scanned = self.start;
+                Some(line)
+            }
+            None => {
+                // A CR at the end may be followed by an LF in the next
+                // buffer.
+                self.scanned = if self.line_break == LineBreak::CrLf && self.text.ends_with('\r') {
+                    self.text.len() - 1
+                } else {
+                    self.text.len()
+                };
+                if !self.ended || self.start == self.text.len() {
+                    return None;
+                }
+                let line = String::from(&self.text[self.start..]);
+                self.start = self.text.len();
+                Some(line)
+            }
+        }
+    }
+}
+
+impl core::fmt::Debug for LineDecoder {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("LineDecoder")
+            .field("decoder", &self.decoder)
+            .field("line_break", &self.line_break)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Iterator over the lines completed by a call to `LineDecoder::decode()`.
+#[derive(Debug)]
+pub struct Lines<'a> {
+    lines: &'a mut LineDecoder,
+}
+
+impl Iterator for Lines<'_> {
+    type Item = String;
+
+    #[inline]
+    fn next(&mut self) -> Option<String> {
+        self.lines.next_line()
+    }
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::super::*;
+    use alloc::vec::Vec;
+
+    fn decode_lines(
+        encoding: &'static Encoding,
+        line_break: LineBreak,
+        bytes: &[u8],
+        chunk: usize,
+    ) -> Vec<String> {
+        let mut decoder = LineDecoder::new(encoding.new_decoder(), line_break);
+        let mut lines = Vec::new();
+        for chunk in bytes.chunks(chunk) {
+            lines.extend(decoder.decode(chunk, false));
+        }
+        lines.extend(decoder.decode(b"", true));
+        lines
+    }
+
+    #[test]
+    fn test_line_breaks() {
+        let text = b"a\nb\r\nc\rd\n\ne";
+        for chunk in [1, 2, 100] {
+            assert_eq!(
+                decode_lines(UTF_8, LineBreak::Lf, text, chunk),
+                ["a", "b\r", "c\rd", "", "e"]
+            );
+            assert_eq!(
+                decode_lines(UTF_8, LineBreak::Cr, text, chunk),
+                ["a\nb", "\nc", "d\n\ne"]
+            );
+            assert_eq!(
+                decode_lines(UTF_8, LineBreak::CrLf, text, chunk),
+                ["a\nb", "c\rd\n\ne"]
+            );
+            assert_eq!(
+                decode_lines(UTF_8, LineBreak::Any, text, chunk),
+                ["a", "b", "c", "d", "", "e"]
+            );
+        }
+        assert_eq!(
+            decode_lines(UTF_8, LineBreak::Any, b"a\r\r\n\nb\r", 1),
+            ["a", "", "", "b"]
+        );
+        assert_eq!(decode_lines(UTF_8, LineBreak::Lf, b"a\n", 1), ["a"]);
+        assert!(decode_lines(UTF_8, LineBreak::Lf, b"", 1).is_empty());
+    }
+
+    #[test]
+    fn test_line_decoder_encodings() {
+        // UTF-16 code units containing 0x0A and 0x0D bytes
+        let bytes = b"\xFF\xFE\x0A\x0D\x0A\x00\x0D\x0A\x0D\x00";
+        for chunk in [1, 3, 100] {
+            assert_eq!(
+                decode_lines(UTF_16LE, LineBreak::Any, bytes, chunk),
+                ["\u{0D0A}", "\u{0A0D}"]
+            );
+        }
+        // Shift_JIS trail bytes aren't line breaks either way, but state
+        // must carry across buffers.
+        let (bytes, _, _) = SHIFT_JIS.encode("行一\n行二\n");
+        assert_eq!(
+            decode_lines(SHIFT_JIS, LineBreak::Lf, &bytes, 1),
+            ["行一", "行二"]
+        );
+        let (bytes, _, _) = ISO_2022_JP.encode("一行目\n二行目\nthird");
+        assert_eq!(
+            decode_lines(ISO_2022_JP, LineBreak::Lf, &bytes, 2),
+            ["一行目", "二行目", "third"]
+        );
+    }
+
+    #[test]
+    fn test_line_decoder_long_line() {
+        let mut text = "x".repeat(10000);
+        text.push_str("\r\r\ny\r");
+        for chunk in [1, 3, 4096] {
+            let lines = decode_lines(UTF_8, LineBreak::CrLf, text.as_bytes(), chunk);
+            assert_eq!(
+                lines,
+                [alloc::format!("{}\r", "x".repeat(10000)), "y\r".into()]
+            );
+        }
+    }
+
+    #[test]
+    fn test_line_decoder_partial_iteration() {
+        let mut decoder = LineDecoder::new(WINDOWS_1252.new_decoder(), LineBreak::Lf);
+        let mut lines = decoder.decode(b"caf\xE9\nb\nc", false);
+        assert_eq!(lines.next().unwrap(), "café");
+        let lines: Vec<String> = decoder.decode(b"\n\xFF", true).collect();
+        assert_eq!(lines, ["b", "c", "ÿ"]);
+    }
+}
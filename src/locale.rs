@@ -0,0 +1,453 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+
+/// Splits a locale identifier of the form
+/// `language[_territory][.codeset][@modifier]` into its parts.
+///
+/// Returns `None` if the language part isn't two or three ASCII letters
+/// (or `C` or `POSIX`).
+fn split_posix_locale(locale: &str) -> Option<(&str, &str, Option<&str>, &str)> {
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, modifier),
+        None => (locale, ""),
+    };
+    let (name, codeset) = match rest.split_once('.') {
+        Some((name, codeset)) => (name, Some(codeset)),
+        None => (rest, None),
+    };
+    let (language, territory) = match name.split_once(['_', '-']) {
+        Some((language, territory)) => (language, territory),
+        None => (name, ""),
+    };
+    if language.eq_ignore_ascii_case("c") || language.eq_ignore_ascii_case("posix") {
+        if !territory.is_empty() {
+            return None;
+        }
+    } else if !(language.len() == 2 || language.len() == 3)
+        || !language.bytes().all(|b| b.is_ascii_alphabetic())
+    {
+        return None;
+    }
+    Some((language, territory, codeset, modifier))
+}
+
+/// Returns the encoding of the codeset that glibc uses for a locale whose
+/// name doesn't specify a codeset.
+fn default_posix_codeset(language: &str, territory: &str, modifier: &str) -> &'static Encoding {
+    if modifier.eq_ignore_ascii_case("euro") {
+        return ISO_8859_15;
+    }
+    if language.len() > 3 {
+        // POSIX
+        return WINDOWS_1252;
+    }
+    let mut lower = [0u8; 3];
+    for (l, b) in lower.iter_mut().zip(language.bytes()) {
+        *l = b.to_ascii_lowercase();
+    }
+    match &lower[..language.len()] {
+        b"c" => WINDOWS_1252,
+        b"ja" => EUC_JP,
+        b"ko" => EUC_KR,
+        b"zh" => {
+            if territory.eq_ignore_ascii_case("tw") || territory.eq_ignore_ascii_case("hk") {
+                BIG5
+            } else {
+                GBK
+            }
+        }
+        b"th" => WINDOWS_874,
+        b"ru" => {
+            if territory.eq_ignore_ascii_case("ua") {
+                KOI8_U
+            } else {
+                ISO_8859_5
+            }
+        }
+        b"uk" => KOI8_U,
+        b"be" | b"bg" => WINDOWS_1251,
+        b"mk" => ISO_8859_5,
+        b"el" => ISO_8859_7,
+        b"he" | b"iw" => ISO_8859_8,
+        b"yi" => WINDOWS_1255,
+        b"ar" => ISO_8859_6,
+        b"tr" | b"ku" => WINDOWS_1254,
+        b"bs" | b"cs" | b"hr" | b"hu" | b"pl" | b"ro" | b"sk" | b"sl" => ISO_8859_2,
+        b"lt" | b"lv" | b"mi" => ISO_8859_13,
+        b"mt" => ISO_8859_3,
+        b"cy" => ISO_8859_14,
+        _ => WINDOWS_1252,
+    }
+}
+
+/// Implements `Encoding::for_locale()`.
+pub fn for_posix_locale(locale: &str) -> Option<&'static Encoding> {
+    let (language, territory, codeset, modifier) = split_posix_locale(locale.trim())?;
+    match codeset {
+        Some(codeset) => Encoding::for_label_extended(codeset.as_bytes()).map(|(e, _)| e),
+        None => Some(default_posix_codeset(language, territory, modifier)),
+    }
+}
+
+// The legacy fallback encodings by language from Gecko's
+// localesfallbacks.properties. Languages not listed use windows-1252.
+static FALLBACK_LANGUAGES_SORTED: [&'static str; 32] = [
+    "ar", "ba", "be", "bg", "cs", "el", "et", "fa", "he", "hr", "hu", "ja", "kk", "ko", "ku", "ky",
+    "lt", "lv", "mk", "pl", "ru", "sah", "sk", "sl", "sr", "tg", "th", "tr", "tt", "uk", "vi",
+    "zh",
+];
+
+static ENCODINGS_IN_FALLBACK_LANGUAGES_SORT: [&'static Encoding; 32] = [
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1250_INIT,
+    &ISO_8859_7_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1255_INIT,
+    &WINDOWS_1250_INIT,
+    &ISO_8859_2_INIT,
+    &SHIFT_JIS_INIT,
+    &WINDOWS_1251_INIT,
+    &EUC_KR_INIT,
+    &WINDOWS_1254_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1251_INIT,
+    &ISO_8859_2_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1250_INIT,
+    &ISO_8859_2_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_874_INIT,
+    &WINDOWS_1254_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1258_INIT,
+    &GBK_INIT,
+];
+
+// The legacy fallback encodings by top-level domain. The two-letter country
+// code TLDs correspond to the locales above. Internationalized country code
+// TLDs are in their Punycode form. TLDs not listed use windows-1252.
+static FALLBACK_TLDS_SORTED: [&'static str; 67] = [
+    "ae",
+    "bg",
+    "bh",
+    "by",
+    "cn",
+    "cy",
+    "cz",
+    "dz",
+    "ee",
+    "eg",
+    "gr",
+    "hk",
+    "hr",
+    "hu",
+    "il",
+    "iq",
+    "ir",
+    "jo",
+    "jp",
+    "kg",
+    "kr",
+    "kw",
+    "kz",
+    "lb",
+    "lt",
+    "lv",
+    "ly",
+    "ma",
+    "mk",
+    "mo",
+    "om",
+    "pl",
+    "ps",
+    "qa",
+    "ru",
+    "sa",
+    "sd",
+    "si",
+    "sk",
+    "su",
+    "sy",
+    "th",
+    "tj",
+    "tn",
+    "tr",
+    "tw",
+    "ua",
+    "vn",
+    "xn--3e0b707e",
+    "xn--4dbrk0ce",
+    "xn--90ae",
+    "xn--90ais",
+    "xn--d1alf",
+    "xn--fiqs8s",
+    "xn--fiqz9s",
+    "xn--j1amh",
+    "xn--j6w193g",
+    "xn--kprw13d",
+    "xn--kpry57d",
+    "xn--mgba3a4f16a",
+    "xn--mgbaam7a8h",
+    "xn--mgberp4a5d4ar",
+    "xn--o3cw4h",
+    "xn--p1ai",
+    "xn--qxam",
+    "xn--wgbh1c",
+    "ye",
+];
+
+static ENCODINGS_IN_FALLBACK_TLDS_SORT: [&'static Encoding; 67] = [
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1251_INIT,
+    &GBK_INIT,
+    &ISO_8859_7_INIT,
+    &WINDOWS_1250_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1256_INIT,
+    &ISO_8859_7_INIT,
+    &BIG5_INIT,
+    &WINDOWS_1250_INIT,
+    &ISO_8859_2_INIT,
+    &WINDOWS_1255_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1256_INIT,
+    &SHIFT_JIS_INIT,
+    &WINDOWS_1251_INIT,
+    &EUC_KR_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1257_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1251_INIT,
+    &BIG5_INIT,
+    &WINDOWS_1256_INIT,
+    &ISO_8859_2_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1256_INIT,
+    &ISO_8859_2_INIT,
+    &WINDOWS_1250_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_874_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1254_INIT,
+    &BIG5_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1258_INIT,
+    &EUC_KR_INIT,
+    &WINDOWS_1255_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1251_INIT,
+    &WINDOWS_1251_INIT,
+    &GBK_INIT,
+    &GBK_INIT,
+    &WINDOWS_1251_INIT,
+    &BIG5_INIT,
+    &BIG5_INIT,
+    &BIG5_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_874_INIT,
+    &WINDOWS_1251_INIT,
+    &ISO_8859_7_INIT,
+    &WINDOWS_1256_INIT,
+    &WINDOWS_1256_INIT,
+];
+
+const LONGEST_FALLBACK_TLD_LENGTH: usize = 17; // xn--mgberp4a5d4ar
+
+fn lookup_fallback(
+    key: &[u8],
+    keys: &[&'static str],
+    encodings: &[&'static Encoding],
+) -> Option<&'static Encoding> {
+    match keys.binary_search_by(|probe| probe.as_bytes().cmp(key)) {
+        Ok(i) => Some(encodings[i]),
+        Err(_) => None,
+    }
+}
+
+/// Implements `Encoding::fallback_for_locale()`.
+pub fn fallback_for_locale(locale: &str) -> &'static Encoding {
+    let mut subtags = locale.trim().split(['-', '_', '.', '@']);
+    let language = subtags.next().unwrap_or("");
+    if !(language.len() == 2 || language.len() == 3)
+        || !language.bytes().all(|b| b.is_ascii_alphabetic())
+    {
+        return WINDOWS_1252;
+    }
+    let mut lower = [0u8; 3];
+    for (l, b) in lower.iter_mut().zip(language.bytes()) {
+        *l = b.to_ascii_lowercase();
+    }
+    let language = &lower[..language.len()];
+    if language == b"zh" {
+        // Traditional Chinese is identified by script or by region.
+        for subtag in subtags {
+            if subtag.eq_ignore_ascii_case("hant")
+                || subtag.eq_ignore_ascii_case("tw")
+                || subtag.eq_ignore_ascii_case("hk")
+                || subtag.eq_ignore_ascii_case("mo")
+            {
+                return BIG5;
+            }
+        }
+    }
+    lookup_fallback(
+        language,
+        &FALLBACK_LANGUAGES_SORTED[..],
+        &ENCODINGS_IN_FALLBACK_LANGUAGES_SORT[..],
+    )
+    .unwrap_or(WINDOWS_1252)
+}
+
+/// Implements `Encoding::fallback_for_tld()`.
+pub fn fallback_for_tld(tld: &[u8]) -> &'static Encoding {
+    let tld = tld.strip_suffix(b".").unwrap_or(tld);
+    let tld = match tld.iter().rposition(|&b| b == b'.') {
+        Some(i) => &tld[i + 1..],
+        None => tld,
+    };
+    if tld.len() > LONGEST_FALLBACK_TLD_LENGTH {
+        return WINDOWS_1252;
+    }
+    let mut lower = [0u8; LONGEST_FALLBACK_TLD_LENGTH];
+    for (l, b) in lower.iter_mut().zip(tld.iter()) {
+        *l = b.to_ascii_lowercase();
+    }
+    lookup_fallback(
+        &lower[..tld.len()],
+        &FALLBACK_TLDS_SORTED[..],
+        &ENCODINGS_IN_FALLBACK_TLDS_SORT[..],
+    )
+    .unwrap_or(WINDOWS_1252)
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test_for_locale_codeset() {
+        assert_eq!(Encoding::for_locale("ja_JP.eucJP"), Some(EUC_JP));
+        assert_eq!(Encoding::for_locale("ja_JP.SJIS"), Some(SHIFT_JIS));
+        assert_eq!(Encoding::for_locale("ru_RU.KOI8-R"), Some(KOI8_R));
+        assert_eq!(Encoding::for_locale("zh_TW.Big5"), Some(BIG5));
+        assert_eq!(Encoding::for_locale("zh_HK.big5hkscs"), Some(BIG5));
+        assert_eq!(Encoding::for_locale("zh_CN.gb18030"), Some(GB18030));
+        assert_eq!(Encoding::for_locale("en_US.UTF-8"), Some(UTF_8));
+        assert_eq!(Encoding::for_locale("en_US.utf8"), Some(UTF_8));
+        assert_eq!(Encoding::for_locale("C.UTF-8"), Some(UTF_8));
+        assert_eq!(Encoding::for_locale("C.utf8"), Some(UTF_8));
+        assert_eq!(
+            Encoding::for_locale("en_US.ANSI_X3.4-1968"),
+            Some(WINDOWS_1252)
+        );
+        assert_eq!(
+            Encoding::for_locale("de_DE.ISO-8859-15@euro"),
+            Some(ISO_8859_15)
+        );
+        assert_eq!(Encoding::for_locale("de_DE.UTF-8@euro"), Some(UTF_8));
+        assert_eq!(Encoding::for_locale("th_TH.TIS-620"), Some(WINDOWS_874));
+        assert_eq!(Encoding::for_locale("sr_RS.UTF-8@latin"), Some(UTF_8));
+        assert_eq!(Encoding::for_locale("ka_GE.GEORGIAN-PS"), None);
+    }
+
+    #[test]
+    fn test_for_locale_default_codeset() {
+        assert_eq!(Encoding::for_locale("C"), Some(WINDOWS_1252));
+        assert_eq!(Encoding::for_locale("POSIX"), Some(WINDOWS_1252));
+        assert_eq!(Encoding::for_locale("en_US"), Some(WINDOWS_1252));
+        assert_eq!(Encoding::for_locale("de_DE@euro"), Some(ISO_8859_15));
+        assert_eq!(Encoding::for_locale("ja_JP"), Some(EUC_JP));
+        assert_eq!(Encoding::for_locale("ko_KR"), Some(EUC_KR));
+        assert_eq!(Encoding::for_locale("zh_CN"), Some(GBK));
+        assert_eq!(Encoding::for_locale("zh_TW"), Some(BIG5));
+        assert_eq!(Encoding::for_locale("ru_RU"), Some(ISO_8859_5));
+        assert_eq!(Encoding::for_locale("ru_UA"), Some(KOI8_U));
+        assert_eq!(Encoding::for_locale("pl"), Some(ISO_8859_2));
+        assert_eq!(Encoding::for_locale("sq_AL"), Some(WINDOWS_1252));
+        assert_eq!(Encoding::for_locale("tr_TR"), Some(WINDOWS_1254));
+        assert_eq!(Encoding::for_locale(" el_GR "), Some(ISO_8859_7));
+    }
+
+    #[test]
+    fn test_for_locale_malformed() {
+        assert_eq!(Encoding::for_locale(""), None);
+        assert_eq!(Encoding::for_locale(".UTF-8"), None);
+        assert_eq!(Encoding::for_locale("english"), None);
+        assert_eq!(Encoding::for_locale("C_US"), None);
+        assert_eq!(Encoding::for_locale("e1_US"), None);
+    }
+
+    #[test]
+    fn test_fallback_for_locale() {
+        assert_eq!(Encoding::fallback_for_locale("en-US"), WINDOWS_1252);
+        assert_eq!(Encoding::fallback_for_locale("fi"), WINDOWS_1252);
+        assert_eq!(Encoding::fallback_for_locale("ja"), SHIFT_JIS);
+        assert_eq!(Encoding::fallback_for_locale("ja-JP"), SHIFT_JIS);
+        assert_eq!(Encoding::fallback_for_locale("ja_JP.UTF-8"), SHIFT_JIS);
+        assert_eq!(Encoding::fallback_for_locale("zh-CN"), GBK);
+        assert_eq!(Encoding::fallback_for_locale("zh"), GBK);
+        assert_eq!(Encoding::fallback_for_locale("zh-TW"), BIG5);
+        assert_eq!(Encoding::fallback_for_locale("zh-Hant-HK"), BIG5);
+        assert_eq!(Encoding::fallback_for_locale("zh_HK"), BIG5);
+        assert_eq!(Encoding::fallback_for_locale("RU"), WINDOWS_1251);
+        assert_eq!(Encoding::fallback_for_locale("sah"), WINDOWS_1251);
+        assert_eq!(Encoding::fallback_for_locale("hu-HU"), ISO_8859_2);
+        assert_eq!(Encoding::fallback_for_locale("ko"), EUC_KR);
+        assert_eq!(Encoding::fallback_for_locale(""), WINDOWS_1252);
+        assert_eq!(Encoding::fallback_for_locale("russian"), WINDOWS_1252);
+    }
+
+    #[test]
+    fn test_fallback_for_tld() {
+        assert_eq!(Encoding::fallback_for_tld(b"com"), WINDOWS_1252);
+        assert_eq!(Encoding::fallback_for_tld(b"jp"), SHIFT_JIS);
+        assert_eq!(Encoding::fallback_for_tld(b".jp"), SHIFT_JIS);
+        assert_eq!(Encoding::fallback_for_tld(b"example.co.jp."), SHIFT_JIS);
+        assert_eq!(Encoding::fallback_for_tld(b"CN"), GBK);
+        assert_eq!(Encoding::fallback_for_tld(b"tw"), BIG5);
+        assert_eq!(Encoding::fallback_for_tld(b"ru"), WINDOWS_1251);
+        assert_eq!(Encoding::fallback_for_tld(b"xn--p1ai"), WINDOWS_1251);
+        assert_eq!(Encoding::fallback_for_tld(b"kr"), EUC_KR);
+        assert_eq!(
+            Encoding::fallback_for_tld(b"xn--mgberp4a5d4arx"),
+            WINDOWS_1252
+        );
+        assert_eq!(Encoding::fallback_for_tld(b""), WINDOWS_1252);
+        assert_eq!(Encoding::fallback_for_tld(b"."), WINDOWS_1252);
+    }
+}
@@ -0,0 +1,919 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::data::*;
+use super::variant::*;
+use super::*;
+
+/// The longest byte sequence that decodes to characters without a state
+/// change in between. (Four-byte gb18030.)
+const LONGEST_SEQUENCE: usize = 4;
+
+/// The escape sequences for the ISO-2022-JP states other than the ASCII
+/// state. The sequences that switch to the ASCII state and the alternative
+/// JIS X 0208 escape sequence are omitted in order not to list the same
+/// mappings many times.
+static ISO_2022_JP_ESCAPES: [&[u8]; 4] = [b"", b"\x1B(J", b"\x1B(I", b"\x1B$B"];
+
+static NO_ESCAPES: [&[u8]; 1] = [b""];
+
+/// A byte sequence and the character or characters that it decodes to.
+///
+/// Yielded by the iterator returned from `Encoding::mappings()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    bytes: [u8; 7],
+    bytes_len: u8,
+    chars: [char; 2],
+    chars_len: u8,
+}
+
+impl Mapping {
+    /// The byte sequence.
+    ///
+    /// For ISO-2022-JP, this includes the escape sequence that switches the
+    /// decoder to the state in which the rest of the bytes are interpreted.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.bytes_len as usize]
+    }
+
+    /// The characters that the byte sequence decodes to.
+    ///
+    /// This is always one character except for the four Big5 byte sequences
+    /// that decode to a base character followed by a combining character.
+    #[inline]
+    pub fn chars(&self) -> &[char] {
+        &self.chars[..self.chars_len as usize]
+    }
+}
+
+/// What a candidate byte sequence decodes to according to the index data.
+enum Classification {
+    /// The sequence decodes to the given characters.
+    Valid([char; 2], u8),
+    /// The sequence is a prefix of potentially valid sequences.
+    Pending,
+    /// The sequence is malformed.
+    Invalid,
+}
+
+impl Classification {
+    fn scalar(scalar: u32) -> Classification {
+        match char::from_u32(scalar) {
+            Some(c) => Classification::Valid([c, '\0'], 1),
+            None => Classification::Invalid,
+        }
+    }
+
+    fn bmp(bmp: u16) -> Classification {
+        Classification::scalar(u32::from(bmp))
+    }
+
+    fn pair(base: u16, combining: u16) -> Classification {
+        Classification::Valid(
+            [
+                char::from_u32(u32::from(base)).unwrap_or_else(|| unreachable!()),
+                char::from_u32(u32::from(combining)).unwrap_or_else(|| unreachable!()),
+            ],
+            2,
+        )
+    }
+}
+
+fn classify_single_byte(table: &'static [u16; 128], byte: u8) -> Classification {
+    if byte < 0x80 {
+        return Classification::bmp(u16::from(byte));
+    }
+    match table[usize::from(byte - 0x80)] {
+        0 => Classification::Invalid,
+        bmp => Classification::bmp(bmp),
+    }
+}
+
+fn classify_x_user_defined(byte: u8) -> Classification {
+    if byte < 0x80 {
+        Classification::bmp(u16::from(byte))
+    } else {
+        Classification::bmp(u16::from(byte) + 0xF700)
+    }
+}
+
+fn classify_big5(sequence: &[u8]) -> Classification {
+    let lead = sequence[0];
+    if lead < 0x80 {
+        return Classification::bmp(u16::from(lead));
+    }
+    let lead_minus_offset = lead.wrapping_sub(0x81);
+    if lead_minus_offset > (0xFE - 0x81) {
+        return Classification::Invalid;
+    }
+    let Some(&trail) = sequence.get(1) else {
+        return Classification::Pending;
+    };
+    let trail_minus_offset = match trail {
+        0x40..=0x7E => trail - 0x40,
+        0xA1..=0xFE => trail - 0x62,
+        _ => return Classification::Invalid,
+    };
+    let pointer = usize::from(lead_minus_offset) * 157 + usize::from(trail_minus_offset);
+    let rebased_pointer = pointer.wrapping_sub(942);
+    let low_bits = big5_low_bits(rebased_pointer);
+    if low_bits == 0 {
+        match pointer {
+            1133 => Classification::pair(0x00CA, 0x0304),
+            1135 => Classification::pair(0x00CA, 0x030C),
+            1164 => Classification::pair(0x00EA, 0x0304),
+            1166 => Classification::pair(0x00EA, 0x030C),
+            _ => Classification::Invalid,
+        }
+    } else if big5_is_astral(rebased_pointer) {
+        Classification::scalar(u32::from(low_bits) | 0x20000)
+    } else {
+        Classification::bmp(low_bits)
+    }
+}
+
+fn classify_euc_kr(sequence: &[u8]) -> Classification {
+    let lead = sequence[0];
+    if lead < 0x80 {
+        return Classification::bmp(u16::from(lead));
+    }
+    let lead_minus_offset = lead.wrapping_sub(0x81);
+    if lead_minus_offset > (0xFE - 0x81) {
+        return Classification::Invalid;
+    }
+    let Some(&trail) = sequence.get(1) else {
+        return Classification::Pending;
+    };
+    if lead_minus_offset < 0x20 {
+        // Extension range above KS X 1001
+        let top_trail = match trail {
+            0x41..=0x5A => trail - 0x41,
+            0x61..=0x7A => trail - (6 + 0x41),
+            0x81..=0xFE => trail - (12 + 0x41),
+            _ => return Classification::Invalid,
+        };
+        let top_pointer = usize::from(lead_minus_offset) * (190 - 12) + usize::from(top_trail);
+        return Classification::bmp(cp949_top_hangul_decode(top_pointer as u16));
+    }
+    let trail_minus_offset = trail.wrapping_sub(0xA1);
+    if trail_minus_offset > (0xFE - 0xA1) {
+        // Extension range to the left of KS X 1001
+        let left_trail = match trail {
+            0x41..=0x5A => trail - 0x41,
+            0x61..=0x7A => trail - (6 + 0x41),
+            0x81..=0xA0 => trail - (12 + 0x41),
+            _ => return Classification::Invalid,
+        };
+        let left_pointer =
+            usize::from(lead_minus_offset - 0x20) * (190 - 94 - 12) + usize::from(left_trail);
+        if left_pointer >= (0x45 - 0x20) * (190 - 94 - 12) + 0x12 {
+            return Classification::Invalid;
+        }
+        return Classification::bmp(cp949_left_hangul_decode(left_pointer as u16));
+    }
+    // KS X 1001
+    let ksx_pointer = mul_94(lead_minus_offset - 0x20) + usize::from(trail_minus_offset);
+    let hangul_pointer = ksx_pointer.wrapping_sub((0x2F - 0x20) * 94);
+    let hanja_pointer = ksx_pointer.wrapping_sub((0x49 - 0x20) * 94);
+    let trail_index = usize::from(trail_minus_offset);
+    if hangul_pointer < KSX1001_HANGUL.len() {
+        Classification::bmp(KSX1001_HANGUL[hangul_pointer])
+    } else if ksx_pointer < KSX1001_SYMBOLS.len() {
+        Classification::bmp(KSX1001_SYMBOLS[ksx_pointer])
+    } else if hanja_pointer < KSX1001_HANJA.len() {
+        Classification::bmp(KSX1001_HANJA[hanja_pointer])
+    } else if lead_minus_offset == 0x27 && trail_index < KSX1001_UPPERCASE.len() {
+        match KSX1001_UPPERCASE[trail_index] {
+            0 => Classification::Invalid,
+            bmp => Classification::bmp(bmp),
+        }
+    } else if lead_minus_offset == 0x28 && trail_index < KSX1001_LOWERCASE.len() {
+        Classification::bmp(KSX1001_LOWERCASE[trail_index])
+    } else if lead_minus_offset == 0x25 && trail_index < KSX1001_BOX.len() {
+        Classification::bmp(KSX1001_BOX[trail_index])
+    } else {
+        let other_pointer = ksx_pointer.wrapping_sub(2 * 94);
+        if other_pointer >= 0x039F {
+            return Classification::Invalid;
+        }
+        match ksx1001_other_decode(other_pointer as u16) {
+            // ASCII range means unassigned
+            bmp if bmp < 0x80 => Classification::Invalid,
+            bmp => Classification::bmp(bmp),
+        }
+    }
+}
+
+/// The four-byte gb18030 pointers that map to the BMP.
+const GB18030_BMP_POINTERS: core::ops::RangeInclusive<usize> = 0..=39419;
+
+/// The four-byte gb18030 pointers that map to the astral planes.
+const GB18030_ASTRAL_POINTERS: core::ops::RangeInclusive<usize> = 189_000..=1_237_575;
+
+fn classify_gb18030(sequence: &[u8]) -> Classification {
+    let first = sequence[0];
+    if first < 0x80 {
+        return Classification::bmp(u16::from(first));
+    }
+    if first == 0x80 {
+        return Classification::bmp(0x20AC);
+    }
+    let first_minus_offset = first.wrapping_sub(0x81);
+    if first_minus_offset > (0xFE - 0x81) {
+        return Classification::Invalid;
+    }
+    let Some(&second) = sequence.get(1) else {
+        return Classification::Pending;
+    };
+    let second_minus_offset = second.wrapping_sub(0x30);
+    if second_minus_offset <= (0x39 - 0x30) {
+        return classify_gb18030_four_byte(first_minus_offset, second_minus_offset, sequence);
+    }
+    if first_minus_offset < 0x20 {
+        // GBK ideograph range above GB2312
+        let trail_minus_offset = match second {
+            0x40..=0x7E => second - 0x40,
+            0x80..=0xFE => second - 0x41,
+            _ => return Classification::Invalid,
+        };
+        let pointer = usize::from(first_minus_offset) * 190 + usize::from(trail_minus_offset);
+        return Classification::bmp(gbk_top_ideograph_decode(pointer as u16));
+    }
+    let trail_minus_offset = second.wrapping_sub(0xA1);
+    if trail_minus_offset > (0xFE - 0xA1) {
+        // GBK range on the left
+        let trail_minus_offset = match second {
+            0x40..=0x7E => second - 0x40,
+            0x80..=0xA0 => second - 0x41,
+            _ => return Classification::Invalid,
+        };
+        let left_pointer =
+            usize::from(first_minus_offset - 0x20) * (190 - 94) + usize::from(trail_minus_offset);
+        let gbk_left_ideograph_pointer = left_pointer.wrapping_sub((0x29 - 0x20) * (190 - 94));
+        return if gbk_left_ideograph_pointer < ((0x7D - 0x29) * (190 - 94)) - 5 {
+            Classification::bmp(gbk_left_ideograph_decode(gbk_left_ideograph_pointer as u16))
+        } else if left_pointer < (0x29 - 0x20) * (190 - 94) {
+            Classification::bmp(gbk_other_decode(left_pointer as u16))
+        } else {
+            let bottom_pointer = left_pointer - (((0x7D - 0x20) * (190 - 94)) - 5);
+            Classification::bmp(GBK_BOTTOM[bottom_pointer])
+        };
+    }
+    // GB2312
+    let hanzi_lead = first_minus_offset.wrapping_sub(0x2F);
+    let trail_index = usize::from(trail_minus_offset);
+    let after_greek = usize::from(trail_minus_offset.wrapping_sub(63));
+    if hanzi_lead < (0x77 - 0x2F) {
+        // Level 1 Hanzi, Level 2 Hanzi or one of the 5 PUA code points in
+        // between.
+        Classification::bmp(GB2312_HANZI[mul_94(hanzi_lead) + trail_index])
+    } else if first_minus_offset == 0x20 {
+        Classification::bmp(GB2312_SYMBOLS[trail_index])
+    } else if first_minus_offset == 0x25 && after_greek < GB2312_SYMBOLS_AFTER_GREEK.len() {
+        Classification::bmp(GB2312_SYMBOLS_AFTER_GREEK[after_greek])
+    } else if first_minus_offset == 0x27 && trail_index < GB2312_PINYIN.len() {
+        Classification::bmp(GB2312_PINYIN[trail_index])
+    } else if first_minus_offset > 0x76 {
+        // Bottom PUA
+        Classification::bmp((0xE234 + mul_94(first_minus_offset - 0x77) + trail_index) as u16)
+    } else {
+        Classification::bmp(gb2312_other_decode(
+            (mul_94(first_minus_offset - 0x21) + trail_index) as u16,
+        ))
+    }
+}
+
+/// Classifies a four-byte gb18030 sequence or a prefix of one. Prefixes
+/// are pending only if some of the pointers that they start are mapped, so
+/// that enumeration only visits the pointer ranges.
+fn classify_gb18030_four_byte(
+    first_minus_offset: u8,
+    second_minus_offset: u8,
+    sequence: &[u8],
+) -> Classification {
+    let mut pointer = usize::from(first_minus_offset) * (10 * 126 * 10)
+        + usize::from(second_minus_offset) * (10 * 126);
+    let mut span = 10 * 126;
+    if let Some(&third) = sequence.get(2) {
+        let third_minus_offset = third.wrapping_sub(0x81);
+        if third_minus_offset > (0xFE - 0x81) {
+            return Classification::Invalid;
+        }
+        pointer += usize::from(third_minus_offset) * 10;
+        span = 10;
+    }
+    let Some(&fourth) = sequence.get(3) else {
+        let last = pointer + span - 1;
+        return if pointer <= *GB18030_BMP_POINTERS.end()
+            || (last >= *GB18030_ASTRAL_POINTERS.start()
+                && pointer <= *GB18030_ASTRAL_POINTERS.end())
+        {
+            Classification::Pending
+        } else {
+            Classification::Invalid
+        };
+    };
+    let fourth_minus_offset = fourth.wrapping_sub(0x30);
+    if fourth_minus_offset > (0x39 - 0x30) {
+        return Classification::Invalid;
+    }
+    pointer += usize::from(fourth_minus_offset);
+    if GB18030_BMP_POINTERS.contains(&pointer) {
+        if pointer == 7457 {
+            Classification::bmp(0xE7C7)
+        } else {
+            Classification::bmp(gb18030_range_decode(pointer as u16))
+        }
+    } else if GB18030_ASTRAL_POINTERS.contains(&pointer) {
+        Classification::scalar((pointer - (189_000 - 0x1_0000)) as u32)
+    } else {
+        Classification::Invalid
+    }
+}
+
+/// Looks up a JIS X 0208 pointer in the parts of the index that EUC-JP,
+/// ISO-2022-JP and Shift_JIS share.
+fn classify_jis0208(pointer: usize) -> Classification {
+    let level1_pointer = pointer.wrapping_sub(1410);
+    let level2_pointer = pointer.wrapping_sub(4418);
+    let ibm_pointer = pointer.wrapping_sub(8272);
+    if level1_pointer < JIS0208_LEVEL1_KANJI.len() {
+        Classification::bmp(JIS0208_LEVEL1_KANJI[level1_pointer])
+    } else if level2_pointer < JIS0208_LEVEL2_AND_ADDITIONAL_KANJI.len() {
+        Classification::bmp(JIS0208_LEVEL2_AND_ADDITIONAL_KANJI[level2_pointer])
+    } else if ibm_pointer < IBM_KANJI.len() {
+        Classification::bmp(IBM_KANJI[ibm_pointer])
+    } else if let Some(bmp) = jis0208_symbol_decode(pointer) {
+        Classification::bmp(bmp)
+    } else if let Some(bmp) = jis0208_range_decode(pointer) {
+        Classification::bmp(bmp)
+    } else {
+        Classification::Invalid
+    }
+}
+
+/// Classifies a JIS X 0208 byte pair with the offsets of EUC-JP or
+/// ISO-2022-JP already subtracted.
+fn classify_jis0208_pair(lead_minus_offset: u8, trail_minus_offset: u8) -> Classification {
+    if lead_minus_offset == 0x03 && trail_minus_offset < 0x53 {
+        // Hiragana
+        Classification::bmp(0x3041 + u16::from(trail_minus_offset))
+    } else if lead_minus_offset == 0x04 && trail_minus_offset < 0x56 {
+        // Katakana
+        Classification::bmp(0x30A1 + u16::from(trail_minus_offset))
+    } else if trail_minus_offset > (0xFE - 0xA1) {
+        Classification::Invalid
+    } else {
+        classify_jis0208(mul_94(lead_minus_offset) + usize::from(trail_minus_offset))
+    }
+}
+
+fn classify_shift_jis(sequence: &[u8]) -> Classification {
+    let lead = sequence[0];
+    let lead_minus_offset = match lead {
+        0x00..=0x80 => return Classification::bmp(u16::from(lead)),
+        0xA1..=0xDF => return Classification::bmp(0xFF61 + u16::from(lead - 0xA1)),
+        0x81..=0x9F => lead - 0x81,
+        0xE0..=0xFC => lead - 0xC1,
+        _ => return Classification::Invalid,
+    };
+    let Some(&trail) = sequence.get(1) else {
+        return Classification::Pending;
+    };
+    let trail_minus_hiragana = trail.wrapping_sub(0x9F);
+    if lead_minus_offset == 0x01 && trail_minus_hiragana < 0x53 {
+        return Classification::bmp(0x3041 + u16::from(trail_minus_hiragana));
+    }
+    let trail_minus_offset = match trail {
+        0x40..=0x7E => trail - 0x40,
+        0x80..=0xFC => trail - 0x41,
+        _ => return Classification::Invalid,
+    };
+    if lead_minus_offset == 0x02 && trail_minus_offset < 0x56 {
+        return Classification::bmp(0x30A1 + u16::from(trail_minus_offset));
+    }
+    let pointer = usize::from(lead_minus_offset) * 188 + usize::from(trail_minus_offset);
+    let upper_ibm_pointer = pointer.wrapping_sub(10744);
+    if upper_ibm_pointer < IBM_KANJI.len() {
+        Classification::bmp(IBM_KANJI[upper_ibm_pointer])
+    } else if (8836..=10715).contains(&pointer) {
+        Classification::bmp((0xE000 - 8836 + pointer) as u16)
+    } else {
+        classify_jis0208(pointer)
+    }
+}
+
+fn classify_euc_jp(sequence: &[u8]) -> Classification {
+    let lead = sequence[0];
+    match lead {
+        0x00..=0x7F => Classification::bmp(u16::from(lead)),
+        0x8E => {
+            let Some(&trail) = sequence.get(1) else {
+                return Classification::Pending;
+            };
+            match trail {
+                0xA1..=0xDF => Classification::bmp(0xFF61 + u16::from(trail - 0xA1)),
+                _ => Classification::Invalid,
+            }
+        }
+        0x8F => {
+            let Some(&jis0212_lead) = sequence.get(1) else {
+                return Classification::Pending;
+            };
+            let jis0212_lead_minus_offset = jis0212_lead.wrapping_sub(0xA1);
+            if jis0212_lead_minus_offset > (0xFE - 0xA1) {
+                return Classification::Invalid;
+            }
+            let Some(&trail) = sequence.get(2) else {
+                return Classification::Pending;
+            };
+            let trail_minus_offset = trail.wrapping_sub(0xA1);
+            if trail_minus_offset > (0xFE - 0xA1) {
+                return Classification::Invalid;
+            }
+            let pointer = mul_94(jis0212_lead_minus_offset) + usize::from(trail_minus_offset);
+            let pointer_minus_kanji = pointer.wrapping_sub(1410);
+            if pointer_minus_kanji < JIS0212_KANJI.len() {
+                Classification::bmp(JIS0212_KANJI[pointer_minus_kanji])
+            } else if let Some(bmp) = jis0212_accented_decode(pointer) {
+                Classification::bmp(bmp)
+            } else if (597..=607).contains(&pointer) {
+                Classification::bmp(0x0402 + (pointer - 597) as u16)
+            } else if (645..=655).contains(&pointer) {
+                Classification::bmp(0x0452 + (pointer - 645) as u16)
+            } else {
+                Classification::Invalid
+            }
+        }
+        0xA1..=0xFE => {
+            let Some(&trail) = sequence.get(1) else {
+                return Classification::Pending;
+            };
+            classify_jis0208_pair(lead - 0xA1, trail.wrapping_sub(0xA1))
+        }
+        _ => Classification::Invalid,
+    }
+}
+
+fn classify_iso_2022_jp(escape: usize, sequence: &[u8]) -> Classification {
+    let first = sequence[0];
+    // Don't explore escape sequences and don't list the line breaks that
+    // return to the ASCII state more than once.
+    if first == 0x1B || (escape != 0 && first == b'\n') {
+        return Classification::Invalid;
+    }
+    match escape {
+        // ASCII and JIS X 0201 Roman
+        0 | 1 => match first {
+            0x5C if escape == 1 => Classification::bmp(0x00A5),
+            0x7E if escape == 1 => Classification::bmp(0x203E),
+            0x0E | 0x0F | 0x80..=0xFF => Classification::Invalid,
+            _ => Classification::bmp(u16::from(first)),
+        },
+        // JIS X 0201 Katakana
+        2 => match first {
+            0x21..=0x5F => Classification::bmp(0xFF61 + u16::from(first - 0x21)),
+            _ => Classification::Invalid,
+        },
+        // JIS X 0208
+        _ => {
+            if !(0x21..=0x7E).contains(&first) {
+                return Classification::Invalid;
+            }
+            let Some(&trail) = sequence.get(1) else {
+                return Classification::Pending;
+            };
+            classify_jis0208_pair(first - 0x21, trail.wrapping_sub(0x21))
+        }
+    }
+}
+
+enum MappingsState {
+    /// Generate the mappings of the Unicode encodings from scalar values.
+    Unicode(u32),
+    /// Walk the byte sequences depth-first and look them up in the index
+    /// data.
+    Bytes {
+        escape: usize,
+        sequence: [u8; LONGEST_SEQUENCE],
+        len: usize,
+    },
+    Done,
+}
+
+/// An iterator over the mappings of an encoding.
+///
+/// Returned by `Encoding::mappings()`.
+pub struct Mappings {
+    encoding: &'static Encoding,
+    escapes: &'static [&'static [u8]],
+    state: MappingsState,
+}
+
+impl Mappings {
+    pub(crate) fn new(encoding: &'static Encoding) -> Mappings {
+        let state = if encoding == REPLACEMENT {
+            MappingsState::Done
+        } else if encoding == UTF_8 || encoding == UTF_16LE || encoding == UTF_16BE {
+            MappingsState::Unicode(0)
+        } else {
+            MappingsState::Bytes {
+                escape: 0,
+                sequence: [0u8; LONGEST_SEQUENCE],
+                len: 1,
+            }
+        };
+        Mappings {
+            encoding,
+            escapes: if encoding == ISO_2022_JP {
+                &ISO_2022_JP_ESCAPES[..]
+            } else {
+                &NO_ESCAPES[..]
+            },
+            state,
+        }
+    }
+
+    /// The range of bytes that can occur at the given position of a
+    /// sequence. Only the third and fourth bytes of four-byte gb18030 are
+    /// limited.
+    fn byte_range_at(&self, position: usize) -> (u8, u8) {
+        match (position, &self.encoding.variant) {
+            (2, VariantEncoding::Gb18030 | VariantEncoding::Gbk) => (0x81, 0xFE),
+            (3, VariantEncoding::Gb18030 | VariantEncoding::Gbk) => (b'0', b'9'),
+            _ => (0, 0xFF),
+        }
+    }
+
+    fn classify(&self, escape: usize, sequence: &[u8]) -> Classification {
+        match self.encoding.variant {
+            VariantEncoding::SingleByte(table, _, _, _) => classify_single_byte(table, sequence[0]),
+            VariantEncoding::UserDefined => classify_x_user_defined(sequence[0]),
+            VariantEncoding::Big5 => classify_big5(sequence),
+            VariantEncoding::EucKr => classify_euc_kr(sequence),
+            VariantEncoding::Gbk | VariantEncoding::Gb18030 => classify_gb18030(sequence),
+            VariantEncoding::ShiftJis => classify_shift_jis(sequence),
+            VariantEncoding::EucJp => classify_euc_jp(sequence),
+            VariantEncoding::Iso2022Jp => classify_iso_2022_jp(escape, sequence),
+            VariantEncoding::Utf8
+            | VariantEncoding::Utf16Be
+            | VariantEncoding::Utf16Le
+            | VariantEncoding::Replacement => unreachable!(),
+        }
+    }
+
+    /// Moves to the next sequence that isn't an extension of the current
+    /// one.
+    fn advance(&mut self) {
+        let last_bytes = [
+            self.byte_range_at(0).1,
+            self.byte_range_at(1).1,
+            self.byte_range_at(2).1,
+            self.byte_range_at(3).1,
+        ];
+        let escapes_len = self.escapes.len();
+        if let MappingsState::Bytes {
+            ref mut escape,
+            ref mut sequence,
+            ref mut len,
+        } = self.state
+        {
+            loop {
+                if sequence[*len - 1] < last_bytes[*len - 1] {
+                    sequence[*len - 1] += 1;
+                    return;
+                }
+                if *len == 1 {
+                    break;
+                }
+                *len -= 1;
+            }
+            *escape += 1;
+            if *escape == escapes_len {
+                self.state = MappingsState::Done;
+            } else {
+                sequence[0] = 0;
+            }
+        }
+    }
+
+    fn next_unicode(&mut self, scalar: u32) -> Option<Mapping> {
+        let c = char::from_u32(scalar)?;
+        self.state = match scalar {
+            0xD7FF => MappingsState::Unicode(0xE000),
+            0x10FFFF => MappingsState::Done,
+            _ => MappingsState::Unicode(scalar + 1),
+        };
+        let mut mapping = Mapping {
+            bytes: [0u8; 7],
+            bytes_len: 0,
+            chars: [c, '\0'],
+            chars_len: 1,
+        };
+        if self.encoding == UTF_8 {
+            mapping.bytes_len = c.encode_utf8(&mut mapping.bytes[..]).len() as u8;
+        } else {
+            let mut units = [0u16; 2];
+            let units = c.encode_utf16(&mut units[..]);
+            for (i, unit) in units.iter().enumerate() {
+                let pair = if self.encoding == UTF_16LE {
+                    unit.to_le_bytes()
+                } else {
+                    unit.to_be_bytes()
+                };
+                mapping.bytes[i * 2..i * 2 + 2].copy_from_slice(&pair);
+            }
+            mapping.bytes_len = (units.len() * 2) as u8;
+        }
+        Some(mapping)
+    }
+}
+
+impl Iterator for Mappings {
+    type Item = Mapping;
+
+    fn next(&mut self) -> Option<Mapping> {
+        loop {
+            let (escape, sequence, len) = match self.state {
+                MappingsState::Done => {
+                    return None;
+                }
+                MappingsState::Unicode(scalar) => {
+                    return self.next_unicode(scalar);
+                }
+                MappingsState::Bytes {
+                    escape,
+                    sequence,
+                    len,
+                } => (escape, sequence, len),
+            };
+            match self.classify(escape, &sequence[..len]) {
+                Classification::Valid(chars, chars_len) => {
+                    let escape = self.escapes[escape];
+                    let mut mapping = Mapping {
+                        bytes: [0u8; 7],
+                        bytes_len: (escape.len() + len) as u8,
+                        chars,
+                        chars_len,
+                    };
+                    mapping.bytes[..escape.len()].copy_from_slice(escape);
+                    mapping.bytes[escape.len()..escape.len() + len]
+                        .copy_from_slice(&sequence[..len]);
+                    self.advance();
+                    return Some(mapping);
+                }
+                Classification::Pending if len < LONGEST_SEQUENCE => {
+                    let (first, _) = self.byte_range_at(len);
+                    if let MappingsState::Bytes {
+                        ref mut sequence,
+                        ref mut len,
+                        ..
+                    } = self.state
+                    {
+                        sequence[*len] = first;
+                        *len += 1;
+                    }
+                }
+                Classification::Pending | Classification::Invalid => {
+                    self.advance();
+                }
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for Mappings {}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::super::*;
+    use super::ISO_2022_JP_ESCAPES;
+    use alloc::vec::Vec;
+
+    type DecodedSequence = (Vec<u8>, Vec<char>);
+
+    /// Lists the mappings of an encoding by running the decoder on every
+    /// candidate byte sequence depth-first, which is slow but independent
+    /// of the index lookups in `Mappings`.
+    fn decoder_mappings(encoding: &'static Encoding) -> Vec<DecodedSequence> {
+        let escapes: &[&[u8]] = if encoding == ISO_2022_JP {
+            &ISO_2022_JP_ESCAPES[..]
+        } else {
+            &[b""]
+        };
+        let mut mappings = Vec::new();
+        for escape in escapes {
+            let mut bytes = escape.to_vec();
+            probe(encoding, escape.len(), &mut bytes, &mut mappings);
+        }
+        mappings
+    }
+
+    fn probe(
+        encoding: &'static Encoding,
+        escape_len: usize,
+        bytes: &mut Vec<u8>,
+        mappings: &mut Vec<DecodedSequence>,
+    ) {
+        let position = bytes.len() - escape_len;
+        let four_byte_gb18030 = position == 3 && (encoding == GB18030 || encoding == GBK);
+        let range = if four_byte_gb18030 {
+            b'0'..=b'9'
+        } else {
+            0..=0xFF
+        };
+        for byte in range {
+            if encoding == ISO_2022_JP
+                && position == 0
+                && (byte == 0x1B || (escape_len != 0 && byte == b'\n'))
+            {
+                continue;
+            }
+            bytes.push(byte);
+            let mut decoder = encoding.new_decoder_without_bom_handling();
+            let mut out = [0u8; 16];
+            let (result, _, written) =
+                decoder.decode_to_utf8_without_replacement(bytes, &mut out, true);
+            match result {
+                DecoderResult::InputEmpty if written != 0 => {
+                    let s = core::str::from_utf8(&out[..written]).unwrap();
+                    mappings.push((bytes.clone(), s.chars().collect()));
+                }
+                DecoderResult::Malformed(_, _) if position + 1 < 4 => {
+                    let mut decoder = encoding.new_decoder_without_bom_handling();
+                    let (result, _, written) =
+                        decoder.decode_to_utf8_without_replacement(bytes, &mut out, false);
+                    if result == DecoderResult::InputEmpty && written == 0 {
+                        probe(encoding, escape_len, bytes, mappings);
+                    }
+                }
+                _ => {}
+            }
+            bytes.pop();
+        }
+    }
+
+    #[test]
+    fn test_mappings_match_decoder() {
+        for &encoding in Encoding::all() {
+            if encoding == UTF_8 || encoding == UTF_16BE || encoding == UTF_16LE {
+                continue;
+            }
+            let expected = decoder_mappings(encoding);
+            let mut mappings = encoding.mappings();
+            for (bytes, chars) in &expected {
+                let mapping = mappings.next().unwrap();
+                assert_eq!(
+                    (mapping.bytes(), mapping.chars()),
+                    (&bytes[..], &chars[..]),
+                    "{}",
+                    encoding.name()
+                );
+            }
+            assert_eq!(mappings.next(), None, "{}", encoding.name());
+        }
+    }
+
+    #[test]
+    fn test_single_byte_mappings() {
+        for encoding in Encoding::all() {
+            if !encoding.is_single_byte() {
+                continue;
+            }
+            let mut count = 0;
+            for mapping in encoding.mappings() {
+                assert_eq!(mapping.bytes().len(), 1);
+                assert_eq!(mapping.chars().len(), 1);
+                let (cow, had_errors) = encoding.decode_without_bom_handling(mapping.bytes());
+                assert!(!had_errors);
+                assert_eq!(cow.chars().collect::<Vec<char>>(), mapping.chars());
+                count += 1;
+            }
+            assert!(count > 200);
+        }
+        assert_eq!(WINDOWS_1252.mappings().count(), 256);
+        assert_eq!(X_USER_DEFINED.mappings().count(), 256);
+        assert_eq!(WINDOWS_1253.mappings().count(), 256 - 3);
+    }
+
+    #[test]
+    fn test_multi_byte_mappings() {
+        let big5: Vec<Mapping> = BIG5.mappings().collect();
+        let two_char = big5.iter().filter(|m| m.chars().len() == 2).count();
+        assert_eq!(two_char, 4);
+        assert!(
+            big5.iter()
+                .any(|m| m.bytes() == b"\x88\x62" && m.chars() == ['\u{00CA}', '\u{0304}'])
+        );
+        assert!(
+            big5.iter()
+                .any(|m| m.bytes() == b"\xA4\x40" && m.chars() == ['\u{4E00}'])
+        );
+
+        let shift_jis: Vec<Mapping> = SHIFT_JIS.mappings().collect();
+        assert!(
+            shift_jis
+                .iter()
+                .any(|m| m.bytes() == b"\xA1" && m.chars() == ['\u{FF61}'])
+        );
+        assert!(
+            shift_jis
+                .iter()
+                .any(|m| m.bytes() == b"\x82\xA0" && m.chars() == ['\u{3042}'])
+        );
+        assert!(!shift_jis.iter().any(|m| m.bytes() == b"\x82"));
+
+        let euc_jp: Vec<Mapping> = EUC_JP.mappings().collect();
+        assert!(
+            euc_jp
+                .iter()
+                .any(|m| m.bytes() == b"\x8F\xB0\xA1" && m.chars() == ['\u{4E02}'])
+        );
+        assert!(
+            euc_jp
+                .iter()
+                .any(|m| m.bytes() == b"\x8E\xA1" && m.chars() == ['\u{FF61}'])
+        );
+
+        for m in EUC_KR.mappings() {
+            let (cow, had_errors) = EUC_KR.decode_without_bom_handling(m.bytes());
+            assert!(!had_errors);
+            assert_eq!(cow.chars().collect::<Vec<char>>(), m.chars());
+        }
+    }
+
+    #[test]
+    fn test_gb18030_mappings() {
+        let mut iter = GB18030.mappings();
+        let first: Vec<Mapping> = iter.by_ref().take(0x82).collect();
+        assert_eq!(first[0x80].bytes(), b"\x80");
+        assert_eq!(first[0x80].chars(), ['\u{20AC}']);
+        assert_eq!(first[0x81].bytes(), b"\x81\x30\x81\x30");
+        assert_eq!(first[0x81].chars(), ['\u{0080}']);
+        assert!(
+            GB18030
+                .mappings()
+                .any(|m| m.bytes() == b"\x90\x30\x81\x30" && m.chars() == ['\u{10000}'])
+        );
+    }
+
+    #[test]
+    fn test_iso_2022_jp_mappings() {
+        let mappings: Vec<Mapping> = ISO_2022_JP.mappings().collect();
+        assert!(
+            mappings
+                .iter()
+                .any(|m| m.bytes() == b"A" && m.chars() == ['A'])
+        );
+        assert!(
+            mappings
+                .iter()
+                .any(|m| m.bytes() == b"\x1B(J\x5C" && m.chars() == ['\u{A5}'])
+        );
+        assert!(
+            mappings
+                .iter()
+                .any(|m| m.bytes() == b"\x1B(I\x21" && m.chars() == ['\u{FF61}'])
+        );
+        assert!(
+            mappings
+                .iter()
+                .any(|m| m.bytes() == b"\x1B$B\x24\x22" && m.chars() == ['\u{3042}'])
+        );
+        assert!(
+            !mappings
+                .iter()
+                .any(|m| m.bytes()[0] == 0x1B && m.chars() == ['\n'])
+        );
+        for m in &mappings {
+            let (cow, had_errors) = ISO_2022_JP.decode_without_bom_handling(m.bytes());
+            assert!(!had_errors);
+            assert_eq!(cow.chars().collect::<Vec<char>>(), m.chars());
+        }
+    }
+
+    #[test]
+    fn test_unicode_mappings() {
+        assert_eq!(UTF_8.mappings().count(), 0x110000 - 0x800);
+        let mut iter = UTF_16LE.mappings();
+        let first = iter.next().unwrap();
+        assert_eq!(first.bytes(), b"\x00\x00");
+        let last = iter.last().unwrap();
+        assert_eq!(last.bytes(), b"\xFF\xDB\xFF\xDF");
+        assert_eq!(last.chars(), ['\u{10FFFF}']);
+        let m = UTF_16BE.mappings().nth(0xE9).unwrap();
+        assert_eq!(m.bytes(), b"\x00\xE9");
+        assert_eq!(REPLACEMENT.mappings().count(), 0);
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+
+/// The longest input to `for_label()` that can match after removing
+/// leading whitespace. Longer labels fail anyway, so unescaping a quoted
+/// `charset` value can stop there.
+const LABEL_BUFFER_LENGTH: usize = 64;
+
+#[inline(always)]
+fn is_http_whitespace(b: u8) -> bool {
+    matches!(b, b'\t' | b'\n' | b'\r' | b' ')
+}
+
+#[inline(always)]
+fn is_http_token(b: u8) -> bool {
+    b.is_ascii_alphanumeric()
+        || matches!(
+            b,
+            b'!' | b'#'
+                | b'$'
+                | b'%'
+                | b'&'
+                | b'\''
+                | b'*'
+                | b'+'
+                | b'-'
+                | b'.'
+                | b'^'
+                | b'_'
+                | b'`'
+                | b'|'
+                | b'~'
+        )
+}
+
+#[inline(always)]
+fn is_http_quoted_string_token(b: u8) -> bool {
+    b == b'\t' || (b >= b' ' && b != 0x7F)
+}
+
+fn trim_http_whitespace_end(bytes: &[u8]) -> &[u8] {
+    let len = bytes
+        .iter()
+        .rposition(|&b| !is_http_whitespace(b))
+        .map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+/// Returns the length of the HTTP quoted string at the start of the
+/// argument, which must start with `"`. The length includes the closing
+/// `"`, if any.
+fn quoted_string_len(bytes: &[u8]) -> usize {
+    debug_assert_eq!(bytes.first(), Some(&b'"'));
+    let mut pos = 1;
+    while let Some(&b) = bytes.get(pos) {
+        pos += 1;
+        match b {
+            b'"' => break,
+            b'\\' if pos < bytes.len() => pos += 1,
+            _ => {}
+        }
+    }
+    pos
+}
+
+/// Iterates over the value of a quoted string given without the opening
+/// quote but with the closing quote, if any.
+fn unescape_quoted_string(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    let mut iter = bytes.iter().copied();
+    core::iter::from_fn(move || match iter.next()? {
+        b'"' => None,
+        b'\\' => Some(iter.next().unwrap_or(b'\\')),
+        b => Some(b),
+    })
+}
+
+/// Resolves an unescaped parameter value using `for_label()`.
+fn label_from_quoted_string(bytes: &[u8]) -> Option<&'static Encoding> {
+    let mut buffer = [0u8; LABEL_BUFFER_LENGTH];
+    let mut len = 0;
+    for b in unescape_quoted_string(bytes) {
+        if len == 0 && is_http_whitespace(b) {
+            continue;
+        }
+        if len == buffer.len() {
+            if is_http_whitespace(b) {
+                continue;
+            }
+            return None;
+        }
+        buffer[len] = b;
+        len += 1;
+    }
+    Encoding::for_label(&buffer[..len])
+}
+
+/// The parts of a parsed MIME type that matter here.
+struct MimeType<'a> {
+    type_: &'a [u8],
+    subtype: &'a [u8],
+    /// `None` if there is no `charset` parameter, `Some(None)` if the label
+    /// wasn't recognized.
+    charset: Option<Option<&'static Encoding>>,
+}
+
+impl MimeType<'_> {
+    fn has_same_essence(&self, other: &MimeType) -> bool {
+        self.type_.eq_ignore_ascii_case(other.type_)
+            && self.subtype.eq_ignore_ascii_case(other.subtype)
+    }
+}
+
+/// The "parse a MIME type" algorithm from the MIME Sniffing Standard.
+fn parse_mime_type(input: &[u8]) -> Option<MimeType<'_>> {
+    let start = input
+        .iter()
+        .position(|&b| !is_http_whitespace(b))
+        .unwrap_or(input.len());
+    let input = trim_http_whitespace_end(&input[start..]);
+
+    let slash = input.iter().position(|&b| b == b'/')?;
+    let type_ = &input[..slash];
+    if type_.is_empty() || !type_.iter().all(|&b| is_http_token(b)) {
+        return None;
+    }
+    let mut pos = slash + 1;
+    let subtype_len = input[pos..]
+        .iter()
+        .position(|&b| b == b';')
+        .unwrap_or(input.len() - pos);
+    let subtype = trim_http_whitespace_end(&input[pos..pos + subtype_len]);
+    if subtype.is_empty() || !subtype.iter().all(|&b| is_http_token(b)) {
+        return None;
+    }
+    pos += subtype_len;
+
+    let mut charset = None;
+    while pos < input.len() {
+        // Skip the `;`.
+        pos += 1;
+        while input.get(pos).is_some_and(|&b| is_http_whitespace(b)) {
+            pos += 1;
+        }
+        let name_start = pos;
+        while input.get(pos).is_some_and(|&b| b != b';' && b != b'=') {
+            pos += 1;
+        }
+        let name = &input[name_start..pos];
+        match input.get(pos) {
+            Some(b';') => continue,
+            Some(_) => pos += 1,
+            None => break,
+        }
+        if pos == input.len() {
+            break;
+        }
+        let is_charset = charset.is_none() && name.eq_ignore_ascii_case(b"charset");
+        if input[pos] == b'"' {
+            let quoted_len = quoted_string_len(&input[pos..]);
+            let quoted = &input[pos + 1..pos + quoted_len];
+            pos += quoted_len;
+            while input.get(pos).is_some_and(|&b| b != b';') {
+                pos += 1;
+            }
+            if is_charset && unescape_quoted_string(quoted).all(is_http_quoted_string_token) {
+                charset = Some(label_from_quoted_string(quoted));
+            }
+        } else {
+            let value_start = pos;
+            while input.get(pos).is_some_and(|&b| b != b';') {
+                pos += 1;
+            }
+            let value = trim_http_whitespace_end(&input[value_start..pos]);
+            if is_charset
+                && !value.is_empty()
+                && value.iter().all(|&b| is_http_quoted_string_token(b))
+            {
+                charset = Some(Encoding::for_label(value));
+            }
+        }
+    }
+    Some(MimeType {
+        type_,
+        subtype,
+        charset,
+    })
+}
+
+/// Splits a header value on commas outside quoted strings as in the
+/// "get, decode, and split" algorithm from the Fetch Standard.
+fn split_header_value(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut rest = Some(input);
+    core::iter::from_fn(move || {
+        let bytes = rest?;
+        let mut pos = 0;
+        loop {
+            match bytes.get(pos) {
+                Some(b'"') => pos += quoted_string_len(&bytes[pos..]),
+                Some(b',') => {
+                    rest = Some(&bytes[pos + 1..]);
+                    break;
+                }
+                Some(_) => pos += 1,
+                None => {
+                    rest = None;
+                    break;
+                }
+            }
+        }
+        let value = &bytes[..pos];
+        let start = value
+            .iter()
+            .position(|&b| b != b'\t' && b != b' ')
+            .unwrap_or(value.len());
+        let end = value
+            .iter()
+            .rposition(|&b| b != b'\t' && b != b' ')
+            .map_or(start, |i| i + 1);
+        Some(&value[start..end])
+    })
+}
+
+/// The charset part of the "extract a MIME type" algorithm from the Fetch
+/// Standard.
+pub(crate) fn for_content_type(input: &[u8]) -> Option<&'static Encoding> {
+    let mut essence: Option<MimeType> = None;
+    let mut charset = None;
+    let mut result = None;
+    for value in split_header_value(input) {
+        let Some(mime_type) = parse_mime_type(value) else {
+            continue;
+        };
+        if mime_type.type_ == b"*" && mime_type.subtype == b"*" {
+            continue;
+        }
+        if essence
+            .as_ref()
+            .is_some_and(|essence| essence.has_same_essence(&mime_type))
+        {
+            result = mime_type.charset.or(charset);
+        } else {
+            charset = mime_type.charset;
+            result = charset;
+            essence = Some(mime_type);
+        }
+    }
+    result.flatten()
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test_for_content_type() {
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset=Shift_JIS"),
+            Some(SHIFT_JIS)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b" Text/HTML ;foo=bar;CHARSET = gbk"),
+            None
+        );
+        assert_eq!(
+            Encoding::for_content_type(b" Text/HTML ;foo=bar;CHARSET=gbk \t"),
+            Some(GBK)
+        );
+        assert_eq!(Encoding::for_content_type(b"text/html"), None);
+        assert_eq!(Encoding::for_content_type(b"text/html; charset="), None);
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset=bogus"),
+            None
+        );
+        assert_eq!(Encoding::for_content_type(b"charset=gbk"), None);
+        assert_eq!(Encoding::for_content_type(b"text/; charset=gbk"), None);
+        assert_eq!(Encoding::for_content_type(b"te xt/html; charset=gbk"), None);
+        assert_eq!(Encoding::for_content_type(b""), None);
+    }
+
+    #[test]
+    fn test_for_content_type_parameters() {
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset=\"big5\"; charset=gbk"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset=bogus; charset=gbk"),
+            None
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset; charset=gbk"),
+            Some(GBK)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset=\"\\b\\ig5\\\" ignored; charset=gbk"),
+            None
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset=\"\\b\\ig5\" ignored; charset=gbk"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset=\"big5"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset*=utf-8''gbk"),
+            None
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html; charset=\"gbk\x01\"; charset=big5"),
+            Some(BIG5)
+        );
+        let mut padded = b"text/plain; charset=\"".to_vec();
+        padded.extend_from_slice(" ".repeat(100).as_bytes());
+        padded.extend_from_slice(b"gbk");
+        padded.extend_from_slice(" ".repeat(100).as_bytes());
+        padded.push(b'"');
+        assert_eq!(Encoding::for_content_type(&padded), Some(GBK));
+    }
+
+    #[test]
+    fn test_for_content_type_combined() {
+        assert_eq!(
+            Encoding::for_content_type(b"text/plain;charset=gbk, text/html"),
+            None
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html;charset=gbk, text/html"),
+            Some(GBK)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html;charset=gbk, TEXT/HTML;charset=big5"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html;charset=gbk, text/html;charset=big5, text/html"),
+            Some(GBK)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html;charset=gbk, */*, bogus"),
+            Some(GBK)
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html;charset=\"gbk,big5\""),
+            None
+        );
+        assert_eq!(
+            Encoding::for_content_type(b"text/html;x=\",\";charset=gbk"),
+            Some(GBK)
+        );
+    }
+}
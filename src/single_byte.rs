@@ -370,6 +370,10 @@ impl SingleByteEncoder {
         None
     }
 
+    pub fn can_encode(&self, c: char) -> bool {
+        c <= '\u{7F}' || (c <= '\u{FFFF}' && self.encode_u16(c as u16).is_some())
+    }
+
     ascii_compatible_bmp_encoder_function!(
         {
             match self.encode_u16(bmp) {
@@ -0,0 +1,1057 @@
+// Copyright Mozilla Foundation. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::*;
+
+/// The number of bytes the HTML prescan looks at.
+const HTML_PRESCAN_LIMIT: usize = 1024;
+
+#[inline(always)]
+fn is_html_space(b: u8) -> bool {
+    matches!(b, b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+/// Cursor over the prescan input. Running off the end makes the prescan
+/// fail, which is expressed by the accessors returning `None`.
+struct Prescan<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Prescan<'a> {
+    #[inline(always)]
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    #[inline(always)]
+    fn starts_with_ignore_case(&self, pattern: &[u8]) -> bool {
+        match self.bytes.get(self.pos..self.pos + pattern.len()) {
+            Some(tail) => tail.eq_ignore_ascii_case(pattern),
+            None => false,
+        }
+    }
+
+    /// Advances to the first occurrence of `b` at or after the current
+    /// position.
+    fn skip_to(&mut self, b: u8) -> Option<()> {
+        let offset = self.bytes[self.pos..].iter().position(|&c| c == b)?;
+        self.pos += offset;
+        Some(())
+    }
+
+    /// The "get an attribute" algorithm. The outer `None` means running out
+    /// of input, the inner `None` means that there was no attribute.
+    ///
+    /// Instead of building lower-cased copies, the name and the value are
+    /// returned as subslices of the input and compared case-insensitively
+    /// by the caller.
+    fn attribute(&mut self) -> Option<Option<(&'a [u8], &'a [u8])>> {
+        let bytes = self.bytes;
+        loop {
+            let b = self.peek()?;
+            if b == b'>' {
+                return Some(None);
+            }
+            if !(is_html_space(b) || b == b'/') {
+                break;
+            }
+            self.pos += 1;
+        }
+        let name_start = self.pos;
+        loop {
+            match self.peek()? {
+                b'=' if self.pos > name_start => {
+                    let name = &bytes[name_start..self.pos];
+                    self.pos += 1;
+                    return self.attribute_value(name);
+                }
+                b if is_html_space(b) => break,
+                b'/' | b'>' => return Some(Some((&bytes[name_start..self.pos], b""))),
+                _ => self.pos += 1,
+            }
+        }
+        let name = &bytes[name_start..self.pos];
+        while is_html_space(self.peek()?) {
+            self.pos += 1;
+        }
+        if self.peek()? != b'=' {
+            return Some(Some((name, b"")));
+        }
+        self.pos += 1;
+        self.attribute_value(name)
+    }
+
+    fn attribute_value(&mut self, name: &'a [u8]) -> Option<Option<(&'a [u8], &'a [u8])>> {
+        let bytes = self.bytes;
+        while is_html_space(self.peek()?) {
+            self.pos += 1;
+        }
+        match self.peek()? {
+            quote @ (b'"' | b'\'') => {
+                self.pos += 1;
+                let value_start = self.pos;
+                self.skip_to(quote)?;
+                let value = &bytes[value_start..self.pos];
+                self.pos += 1;
+                Some(Some((name, value)))
+            }
+            b'>' => Some(Some((name, b""))),
+            _ => {
+                let value_start = self.pos;
+                self.pos += 1;
+                loop {
+                    let b = self.peek()?;
+                    if is_html_space(b) || b == b'>' {
+                        return Some(Some((name, &bytes[value_start..self.pos])));
+                    }
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Processes a `meta` tag whose attributes start at the current
+    /// position.
+    fn meta(&mut self) -> Option<Option<&'static Encoding>> {
+        let mut seen_http_equiv = false;
+        let mut seen_content = false;
+        let mut seen_charset = false;
+        let mut got_pragma = false;
+        let mut need_pragma = None;
+        let mut charset = None;
+        while let Some((name, value)) = self.attribute()? {
+            if name.eq_ignore_ascii_case(b"http-equiv") {
+                if !seen_http_equiv {
+                    seen_http_equiv = true;
+                    if value.eq_ignore_ascii_case(b"content-type") {
+                        got_pragma = true;
+                    }
+                }
+            } else if name.eq_ignore_ascii_case(b"content") {
+                if !seen_content {
+                    seen_content = true;
+                    if !seen_charset
+                        && charset.is_none()
+                        && let Some(encoding) = charset_from_content(value)
+                    {
+                        charset = Some(encoding);
+                        need_pragma = Some(true);
+                    }
+                }
+            } else if name.eq_ignore_ascii_case(b"charset") && !seen_charset {
+                seen_charset = true;
+                charset = Encoding::for_label(value);
+                need_pragma = Some(false);
+            }
+        }
+        let encoding = match need_pragma {
+            None => None,
+            Some(true) if !got_pragma => None,
+            Some(_) => charset,
+        };
+        Some(encoding.map(|encoding| {
+            if encoding == UTF_16BE || encoding == UTF_16LE {
+                UTF_8
+            } else if encoding == X_USER_DEFINED {
+                WINDOWS_1252
+            } else {
+                encoding
+            }
+        }))
+    }
+
+    /// Skips over the attributes of a tag other than `meta`.
+    fn other_tag(&mut self) -> Option<()> {
+        loop {
+            let b = self.peek()?;
+            if is_html_space(b) || b == b'>' {
+                break;
+            }
+            self.pos += 1;
+        }
+        while self.attribute()?.is_some() {}
+        Some(())
+    }
+
+    fn run(&mut self) -> Option<&'static Encoding> {
+        loop {
+            let b = self.peek()?;
+            if b == b'<' {
+                if self.starts_with_ignore_case(b"<!--") {
+                    // The `-->` may share its dashes with the `<!--`.
+                    self.pos += 2;
+                    loop {
+                        self.skip_to(b'>')?;
+                        if self.bytes[self.pos - 2..self.pos] == *b"--" {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                } else if self.starts_with_ignore_case(b"<meta")
+                    && self
+                        .bytes
+                        .get(self.pos + 5)
+                        .is_some_and(|&b| is_html_space(b) || b == b'/')
+                {
+                    self.pos += 6;
+                    if let Some(encoding) = self.meta()? {
+                        return Some(encoding);
+                    }
+                } else {
+                    let after = self.bytes.get(self.pos + 1).copied();
+                    let letter = if after == Some(b'/') {
+                        self.bytes.get(self.pos + 2).copied()
+                    } else {
+                        after
+                    };
+                    if letter.is_some_and(|b| b.is_ascii_alphabetic()) {
+                        self.pos += 1;
+                        self.other_tag()?;
+                    } else if matches!(after, Some(b'!' | b'/' | b'?')) {
+                        self.skip_to(b'>')?;
+                    }
+                }
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+/// The algorithm for extracting a character encoding from a meta element.
+fn charset_from_content(content: &[u8]) -> Option<&'static Encoding> {
+    let mut pos = 0;
+    loop {
+        let offset = content[pos..]
+            .windows(7)
+            .position(|w| w.eq_ignore_ascii_case(b"charset"))?;
+        pos += offset + 7;
+        while content.get(pos).is_some_and(|&b| is_html_space(b)) {
+            pos += 1;
+        }
+        if content.get(pos) == Some(&b'=') {
+            pos += 1;
+            break;
+        }
+    }
+    while content.get(pos).is_some_and(|&b| is_html_space(b)) {
+        pos += 1;
+    }
+    let rest = &content[pos..];
+    match *rest.first()? {
+        quote @ (b'"' | b'\'') => {
+            let len = rest[1..].iter().position(|&b| b == quote)?;
+            Encoding::for_label(&rest[1..1 + len])
+        }
+        _ => {
+            let len = rest
+                .iter()
+                .position(|&b| is_html_space(b) || b == b';')
+                .unwrap_or(rest.len());
+            Encoding::for_label(&rest[..len])
+        }
+    }
+}
+
+pub(crate) fn prescan_html(bytes: &[u8]) -> Option<&'static Encoding> {
+    let bytes = &bytes[..bytes.len().min(HTML_PRESCAN_LIMIT)];
+    Prescan { bytes, pos: 0 }.run()
+}
+
+#[inline(always)]
+fn is_xml_space(b: u8) -> bool {
+    matches!(b, b'\t' | b'\n' | b'\r' | b' ')
+}
+
+/// Extracts the value of the `encoding` pseudo-attribute from an XML
+/// declaration in an ASCII-compatible encoding.
+fn xml_declared_encoding(bytes: &[u8]) -> Option<&[u8]> {
+    if !bytes.starts_with(b"<?xml") || !is_xml_space(*bytes.get(5)?) {
+        return None;
+    }
+    let declaration = match bytes.windows(2).position(|w| w == b"?>") {
+        Some(end) => &bytes[..end],
+        None => bytes,
+    };
+    let mut pos = 6;
+    loop {
+        let offset = declaration[pos..]
+            .windows(8)
+            .position(|w| w == b"encoding")?;
+        let preceded_by_space = is_xml_space(declaration[pos + offset - 1]);
+        pos += offset + 8;
+        if !preceded_by_space {
+            continue;
+        }
+        while declaration.get(pos).is_some_and(|&b| is_xml_space(b)) {
+            pos += 1;
+        }
+        if declaration.get(pos) != Some(&b'=') {
+            continue;
+        }
+        pos += 1;
+        while declaration.get(pos).is_some_and(|&b| is_xml_space(b)) {
+            pos += 1;
+        }
+        let quote = *declaration.get(pos)?;
+        if quote != b'"' && quote != b'\'' {
+            return None;
+        }
+        let rest = &declaration[pos + 1..];
+        let len = rest.iter().position(|&b| b == quote)?;
+        return Some(&rest[..len]);
+    }
+}
+
+pub(crate) fn sniff_xml(bytes: &[u8]) -> Option<(&'static Encoding, usize)> {
+    match bytes {
+        // UTF-32 and EBCDIC aren't supported by the Encoding Standard.
+        [0x00, 0x00, 0xFE, 0xFF, ..]
+        | [0xFF, 0xFE, 0x00, 0x00, ..]
+        | [0x00, 0x00, 0x00, 0x3C, ..]
+        | [0x3C, 0x00, 0x00, 0x00, ..]
+        | [0x4C, 0x6F, 0xA7, 0x94, ..] => None,
+        [0xEF, 0xBB, 0xBF, ..] => Some((UTF_8, 3)),
+        [0xFE, 0xFF, ..] => Some((UTF_16BE, 2)),
+        [0xFF, 0xFE, ..] => Some((UTF_16LE, 2)),
+        [0x00, 0x3C, 0x00, 0x3F, ..] => Some((UTF_16BE, 0)),
+        [0x3C, 0x00, 0x3F, 0x00, ..] => Some((UTF_16LE, 0)),
+        _ => {
+            let encoding = Encoding::for_label(xml_declared_encoding(bytes)?)?;
+            // An ASCII-compatible declaration can't be right about UTF-16.
+            if encoding == UTF_16BE || encoding == UTF_16LE {
+                Some((UTF_8, 0))
+            } else {
+                Some((encoding, 0))
+            }
+        }
+    }
+}
+
+/// The number of bytes the CSS `@charset` check looks at.
+const CSS_CHARSET_LIMIT: usize = 1024;
+
+pub(crate) fn for_css(
+    bytes: &[u8],
+    protocol_charset: Option<&[u8]>,
+    environment: Option<&'static Encoding>,
+) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if let Some(encoding) = protocol_charset.and_then(Encoding::for_label) {
+        return encoding;
+    }
+    let bytes = &bytes[..bytes.len().min(CSS_CHARSET_LIMIT)];
+    if let Some(rest) = bytes.strip_prefix(b"@charset \"")
+        && let Some(len) = rest.iter().position(|&b| b == b'"' || b == b';')
+        && rest[len..].starts_with(b"\";")
+        && let Some(encoding) = Encoding::for_label(&rest[..len])
+    {
+        // As in CSS Syntax, only the UTF-16 labels are replaced, so the
+        // replacement encoding stays.
+        return if encoding == UTF_16BE || encoding == UTF_16LE {
+            UTF_8
+        } else {
+            encoding
+        };
+    }
+    environment.unwrap_or(UTF_8)
+}
+
+/// The number of bytes `SniffingDecoder` buffers before deciding.
+const SNIFFING_BUFFER_LENGTH: usize = 1024;
+
+/// The maximum number of steps a `SniffingDecoder` runs.
+const MAX_SNIFFING_STEPS: usize = 8;
+
+/// A step for `SniffingDecoder` to run for determining the encoding.
+///
+/// The steps are run in the order given until one of them decides the
+/// encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SniffingStep {
+    /// Decides the encoding with certainty if the input starts with a BOM,
+    /// which is then removed.
+    Bom,
+    /// Decides the given encoding with certainty. Typically the encoding is
+    /// the result of `Encoding::for_content_type()` on the transport-layer
+    /// `Content-Type`.
+    Transport(&'static Encoding),
+    /// Decides the encoding tentatively if `Encoding::prescan_html()` finds
+    /// a declaration.
+    HtmlPrescan,
+    /// Decides the encoding with certainty if `Encoding::sniff_xml()`
+    /// detects one. A detected BOM is removed.
+    XmlDeclaration,
+    /// Decides the encoding tentatively by running an `EncodingDetector` on
+    /// the buffered bytes. Always decides, so steps after this one are never
+    /// run.
+    #[cfg(feature = "detect")]
+    Detector {
+        /// Whether UTF-8 may be guessed.
+        allow_utf8: bool,
+    },
+    /// Decides the given encoding tentatively. Always decides, so steps
+    /// after this one are never run.
+    Fallback(&'static Encoding),
+}
+
+/// How sure a `SniffingDecoder` is about the encoding it decided on, in the
+/// sense of the HTML Standard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confidence {
+    /// The encoding was guessed or declared within the content, so it may
+    /// need changing later.
+    Tentative,
+    /// The encoding came from a BOM or from out-of-band information.
+    Certain,
+}
+
+/// A decoder that buffers the start of the input, runs a sequence of
+/// `SniffingStep`s on the buffered bytes to determine the encoding and
+/// only then starts producing output.
+///
+/// Up to the first 1024 bytes of input are buffered. The steps run when
+/// the buffer is full or when the end of the stream is signaled, whichever
+/// comes first. Until then, input is consumed without producing output.
+/// Once the encoding has been decided, the buffered bytes are decoded
+/// before the rest of the input.
+///
+/// If no step decides, `SniffingStep::Fallback(UTF_8)` is assumed.
+///
+/// The streaming contract of the `decode_*` methods is the same as for
+/// `Decoder`. In particular, the decoder must not be used after a call
+/// with `last` set to `true` has returned `CoderResult::InputEmpty`.
+///
+/// # Example
+/// ```
+/// use encoding_rs::*;
+///
+/// let mut decoder = SniffingDecoder::new(&[
+///     SniffingStep::Bom,
+///     SniffingStep::HtmlPrescan,
+///     SniffingStep::Fallback(WINDOWS_1252),
+/// ]);
+/// let mut dst = String::with_capacity(100);
+/// let (result, read, _) = decoder.decode_to_string(b"<meta charset=koi8-r>", &mut dst, false);
+/// assert_eq!((result, read), (CoderResult::InputEmpty, 21));
+/// assert!(dst.is_empty());
+/// assert_eq!(decoder.encoding(), None);
+/// let (result, read, _) = decoder.decode_to_string(b"\xF0\xD2\xC9\xD7\xC5\xD4", &mut dst, true);
+/// assert_eq!((result, read), (CoderResult::InputEmpty, 6));
+/// assert_eq!(dst, "<meta charset=koi8-r>Привет");
+/// assert_eq!(decoder.encoding(), Some(KOI8_R));
+/// assert_eq!(
+///     decoder.decided_by(),
+///     Some((SniffingStep::HtmlPrescan, Confidence::Tentative))
+/// );
+/// ```
+pub struct SniffingDecoder {
+    steps: [SniffingStep; MAX_SNIFFING_STEPS],
+    steps_len: usize,
+    buffer: [u8; SNIFFING_BUFFER_LENGTH],
+    buffered: usize,
+    replayed: usize,
+    decoder: Option<Decoder>,
+    decided_by: Option<(SniffingStep, Confidence)>,
+}
+
+impl SniffingDecoder {
+    /// Instantiates a decoder that runs the given steps in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than eight steps are given.
+    pub fn new(steps: &[SniffingStep]) -> SniffingDecoder {
+        assert!(
+            steps.len() <= MAX_SNIFFING_STEPS,
+            "Too many sniffing steps."
+        );
+        let mut array = [SniffingStep::Fallback(UTF_8); MAX_SNIFFING_STEPS];
+        array[..steps.len()].copy_from_slice(steps);
+        SniffingDecoder {
+            steps: array,
+            steps_len: steps.len(),
+            buffer: [0; SNIFFING_BUFFER_LENGTH],
+            buffered: 0,
+            replayed: 0,
+            decoder: None,
+            decided_by: None,
+        }
+    }
+
+    /// The encoding that was decided on or `None` if the input is still
+    /// being buffered.
+    #[inline]
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        self.decoder.as_ref().map(Decoder::encoding)
+    }
+
+    /// The step that decided the encoding and the confidence of the
+    /// decision or `None` if the input is still being buffered.
+    ///
+    /// If no step decided, the step is `SniffingStep::Fallback(UTF_8)`.
+    #[inline]
+    pub fn decided_by(&self) -> Option<(SniffingStep, Confidence)> {
+        self.decided_by
+    }
+
+    /// Runs the steps on the buffered bytes.
+    #[cfg_attr(not(feature = "detect"), allow(unused_variables))]
+    fn decide(&mut self, last: bool) {
+        let buffer = &self.buffer[..self.buffered];
+        let mut skip = 0;
+        let (encoding, step, confidence) = self.steps[..self.steps_len]
+            .iter()
+            .find_map(|&step| match step {
+                SniffingStep::Bom => Encoding::for_bom(buffer).map(|(encoding, bom_length)| {
+                    skip = bom_length;
+                    (encoding, step, Confidence::Certain)
+                }),
+                SniffingStep::Transport(encoding) => Some((encoding, step, Confidence::Certain)),
+                SniffingStep::HtmlPrescan => {
+                    prescan_html(buffer).map(|encoding| (encoding, step, Confidence::Tentative))
+                }
+                SniffingStep::XmlDeclaration => sniff_xml(buffer).map(|(encoding, bom_length)| {
+                    skip = bom_length;
+                    (encoding, step, Confidence::Certain)
+                }),
+                #[cfg(feature = "detect")]
+                SniffingStep::Detector { allow_utf8 } => {
+                    let mut detector = EncodingDetector::new();
+                    detector.feed(buffer, last);
+                    Some((
+                        detector.guess(None, allow_utf8),
+                        step,
+                        Confidence::Tentative,
+                    ))
+                }
+                SniffingStep::Fallback(encoding) => Some((encoding, step, Confidence::Tentative)),
+            })
+            .unwrap_or((UTF_8, SniffingStep::Fallback(UTF_8), Confidence::Tentative));
+        self.replayed = skip;
+        self.decoder = Some(encoding.new_decoder_without_bom_handling());
+        self.decided_by = Some((step, confidence));
+    }
+}
+
+macro_rules! sniffing_decode {
+    ($name:ident, $dst_ty:ty, $doc:expr) => {
+        #[doc = $doc]
+        pub fn $name(
+            &mut self,
+            src: &[u8],
+            dst: &mut $dst_ty,
+            last: bool,
+        ) -> (CoderResult, usize, usize, bool) {
+            let mut read = 0;
+            if self.decoder.is_none() {
+                read = core::cmp::min(src.len(), SNIFFING_BUFFER_LENGTH - self.buffered);
+                self.buffer[self.buffered..self.buffered + read].copy_from_slice(&src[..read]);
+                self.buffered += read;
+                let ended = last && read == src.len();
+                if self.buffered < SNIFFING_BUFFER_LENGTH && !ended {
+                    return (CoderResult::InputEmpty, read, 0, false);
+                }
+                self.decide(ended);
+            }
+            // Only `None` before `decide()`.
+            let decoder = self.decoder.as_mut().unwrap();
+            let mut written = 0;
+            let mut had_replacements = false;
+            if self.replayed < self.buffered {
+                let ended = last && read == src.len();
+                let (result, replay_read, replay_written, replaced) = decoder.$name(
+                    &self.buffer[self.replayed..self.buffered],
+                    &mut dst[..],
+                    ended,
+                );
+                self.replayed += replay_read;
+                written = replay_written;
+                had_replacements = replaced;
+                if result == CoderResult::OutputFull {
+                    return (CoderResult::OutputFull, read, written, had_replacements);
+                }
+                if ended {
+                    return (CoderResult::InputEmpty, read, written, had_replacements);
+                }
+            }
+            let (result, rest_read, rest_written, replaced) =
+                decoder.$name(&src[read..], &mut dst[written..], last);
+            (
+                result,
+                read + rest_read,
+                written + rest_written,
+                had_replacements | replaced,
+            )
+        }
+    };
+}
+
+impl SniffingDecoder {
+    sniffing_decode!(
+        decode_to_utf8,
+        [u8],
+        "Incrementally decode a byte stream into UTF-8 with malformed \
+         sequences replaced with the REPLACEMENT CHARACTER. See the \
+         documentation of `Decoder::decode_to_utf8()` for the contract."
+    );
+    sniffing_decode!(
+        decode_to_str,
+        str,
+        "Incrementally decode a byte stream into UTF-8 with malformed \
+         sequences replaced with the REPLACEMENT CHARACTER. See the \
+         documentation of `Decoder::decode_to_str()` for the contract."
+    );
+    sniffing_decode!(
+        decode_to_utf16,
+        [u16],
+        "Incrementally decode a byte stream into UTF-16 with malformed \
+         sequences replaced with the REPLACEMENT CHARACTER. See the \
+         documentation of `Decoder::decode_to_utf16()` for the contract."
+    );
+
+    /// Incrementally decode a byte stream into UTF-8 with malformed
+    /// sequences replaced with the REPLACEMENT CHARACTER with type system
+    /// signaling of UTF-8 validity. See the documentation of
+    /// `Decoder::decode_to_string()` for the contract.
+    #[cfg(feature = "alloc")]
+    pub fn decode_to_string(
+        &mut self,
+        src: &[u8],
+        dst: &mut String,
+        last: bool,
+    ) -> (CoderResult, usize, bool) {
+        // SAFETY: Writing to `String` by using it as `Vec` is safe
+        // iff the result is valid UTF-8 afterwards. We trust
+        // `decode_to_utf8` below to write valid UTF-8 and
+        // we trust that we update the length correctly below.
+        // Furthermore, the length update is the last operation, so
+        // if an earlier step panics, the logically exposed part of the
+        // `Vec`/`String` remains unchanged.
+        let vec = unsafe { dst.as_mut_vec() };
+        let old_len = vec.len();
+        let spare_capacity = minimally_init(vec.spare_capacity_mut());
+        let (result, read, written, replaced) = self.decode_to_utf8(src, spare_capacity, last);
+        debug_assert!(written <= spare_capacity.len());
+        let new_len = old_len + written;
+        assert!(new_len <= vec.capacity());
+        // SAFETY: We trust that `decode_to_utf8` wrote valid UTF-8
+        // to `spare_capacity[..written]`. Also, regarding the information
+        // disclosure risk of `minimally_init`, this also means trusting
+        // that every byte of `spare_capacity[..written]` got overwritten.
+        // (We're no worse off than before regarding
+        // `spare_capacity[written..]`) which remains not logically exposed.)
+        // We (non-debug )asserted immediately above that `new_len` conforms
+        // to the invariant that it must not exceed `vec.capacity()`.
+        unsafe {
+            vec.set_len(new_len);
+        }
+        (result, read, replaced)
+    }
+}
+
+// Any copyright to the test code below this comment is dedicated to the
+// Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test_prescan_html_charset() {
+        assert_eq!(
+            Encoding::prescan_html(b"<meta charset=shift_jis>"),
+            Some(SHIFT_JIS)
+        );
+        assert_eq!(
+            Encoding::prescan_html(b"<!DOCTYPE html><html><head><META CHARSET = \" Big5 \">"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::prescan_html(b"<meta/charset='koi8-r'/>"),
+            Some(KOI8_R)
+        );
+        assert_eq!(Encoding::prescan_html(b"<meta charset=bogus>"), None);
+        assert_eq!(Encoding::prescan_html(b"<meta charset=euc-kr"), None);
+        assert_eq!(Encoding::prescan_html(b"<metacharset=euc-kr>"), None);
+        assert_eq!(Encoding::prescan_html(b""), None);
+    }
+
+    #[test]
+    fn test_prescan_html_http_equiv() {
+        assert_eq!(
+            Encoding::prescan_html(
+                b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=ISO-8859-2\">"
+            ),
+            Some(ISO_8859_2)
+        );
+        assert_eq!(
+            Encoding::prescan_html(
+                b"<meta content='text/html;charset = \"gbk\"' http-equiv=content-type>"
+            ),
+            Some(GBK)
+        );
+        // No pragma
+        assert_eq!(
+            Encoding::prescan_html(b"<meta content=\"text/html; charset=gbk\">"),
+            None
+        );
+        // `charsetx` doesn't match but a later `charset=` does.
+        assert_eq!(
+            Encoding::prescan_html(
+                b"<meta http-equiv=content-type content='charsetx; charset=euc-jp'>"
+            ),
+            Some(EUC_JP)
+        );
+        // Unmatched quote
+        assert_eq!(
+            Encoding::prescan_html(b"<meta http-equiv=content-type content='charset=\"gbk'>"),
+            None
+        );
+        // Duplicate attributes are ignored.
+        assert_eq!(
+            Encoding::prescan_html(b"<meta charset=gbk charset=big5>"),
+            Some(GBK)
+        );
+        // `charset` wins over `content` and drops the need for the pragma.
+        assert_eq!(
+            Encoding::prescan_html(b"<meta content='charset=gbk' charset=big5>"),
+            Some(BIG5)
+        );
+        // An unrecognized `charset` still keeps `content` from counting.
+        assert_eq!(
+            Encoding::prescan_html(
+                b"<meta charset=bogus http-equiv=content-type content=\"text/html; charset=gbk\">"
+            ),
+            None
+        );
+        assert_eq!(
+            Encoding::prescan_html(
+                b"<meta content=\"text/html; charset=gbk\" http-equiv=content-type charset=bogus>"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_prescan_html_skipping() {
+        assert_eq!(
+            Encoding::prescan_html(b"<!-- <meta charset=gbk> --><meta charset=big5>"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::prescan_html(b"<!--><meta charset=big5>"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::prescan_html(
+                b"<!-- <meta charset=gbk> -- ><meta charset=big5> --><meta charset=koi8-r>"
+            ),
+            Some(KOI8_R)
+        );
+        assert_eq!(
+            Encoding::prescan_html(b"<title a='<meta charset=gbk>'><meta charset=big5>"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::prescan_html(b"<?xml <meta charset=gbk>?><meta charset=big5>"),
+            Some(BIG5)
+        );
+        assert_eq!(
+            Encoding::prescan_html(b"</p class='<meta charset=gbk>'><meta charset=big5>"),
+            Some(BIG5)
+        );
+        assert_eq!(Encoding::prescan_html(b"< meta charset=gbk>"), None);
+    }
+
+    #[test]
+    fn test_prescan_html_overrides() {
+        assert_eq!(
+            Encoding::prescan_html(b"<meta charset=utf-16le>"),
+            Some(UTF_8)
+        );
+        assert_eq!(
+            Encoding::prescan_html(b"<meta charset=utf-16>"),
+            Some(UTF_8)
+        );
+        assert_eq!(
+            Encoding::prescan_html(b"<meta charset=x-user-defined>"),
+            Some(WINDOWS_1252)
+        );
+    }
+
+    #[test]
+    fn test_prescan_html_limit() {
+        let mut html = b"<html>".to_vec();
+        html.resize(1000, b' ');
+        html.extend_from_slice(b"<meta charset=gbk>");
+        assert_eq!(Encoding::prescan_html(&html), Some(GBK));
+        html.truncate(1000);
+        html.resize(1010, b' ');
+        html.extend_from_slice(b"<meta charset=gbk>");
+        assert_eq!(Encoding::prescan_html(&html), None);
+    }
+
+    #[test]
+    fn test_sniff_xml_bom_and_patterns() {
+        assert_eq!(
+            Encoding::sniff_xml(b"\xEF\xBB\xBF<?xml version='1.0' encoding='gbk'?>"),
+            Some((UTF_8, 3))
+        );
+        assert_eq!(Encoding::sniff_xml(b"\xFE\xFF\x00<"), Some((UTF_16BE, 2)));
+        assert_eq!(Encoding::sniff_xml(b"\xFF\xFE<\x00"), Some((UTF_16LE, 2)));
+        assert_eq!(
+            Encoding::sniff_xml(b"\x00<\x00?\x00x\x00m\x00l"),
+            Some((UTF_16BE, 0))
+        );
+        assert_eq!(
+            Encoding::sniff_xml(b"<\x00?\x00x\x00m\x00l\x00"),
+            Some((UTF_16LE, 0))
+        );
+        assert_eq!(Encoding::sniff_xml(b"\xFF\xFE\x00\x00<\x00\x00\x00"), None);
+        assert_eq!(Encoding::sniff_xml(b"\x00\x00\x00<\x00\x00\x00?"), None);
+        assert_eq!(Encoding::sniff_xml(b"\x4C\x6F\xA7\x94"), None);
+        assert_eq!(Encoding::sniff_xml(b""), None);
+    }
+
+    #[test]
+    fn test_sniff_xml_declaration() {
+        assert_eq!(
+            Encoding::sniff_xml(b"<?xml version=\"1.0\" encoding=\"Shift_JIS\"?><a/>"),
+            Some((SHIFT_JIS, 0))
+        );
+        assert_eq!(
+            Encoding::sniff_xml(
+                b"<?xml version='1.0'\r\n  encoding = 'iso-8859-1' standalone='yes'?>"
+            ),
+            Some((WINDOWS_1252, 0))
+        );
+        assert_eq!(
+            Encoding::sniff_xml(b"<?xml version='1.0' encoding='UTF-16'?>"),
+            Some((UTF_8, 0))
+        );
+        assert_eq!(
+            Encoding::sniff_xml(b"<?xml version='1.0'?><a encoding='gbk'/>"),
+            None
+        );
+        assert_eq!(
+            Encoding::sniff_xml(b"<?xml version='1.0' encoding='bogus'?>"),
+            None
+        );
+        assert_eq!(
+            Encoding::sniff_xml(b"<?xml version='1.0' encoding=gbk?>"),
+            None
+        );
+        assert_eq!(
+            Encoding::sniff_xml(b"<?xml version='1.0' encoding='gbk"),
+            None
+        );
+        assert_eq!(
+            Encoding::sniff_xml(b"<?xml-stylesheet encoding='gbk'?>"),
+            None
+        );
+        assert_eq!(Encoding::sniff_xml(b"<a/>"), None);
+    }
+
+    #[test]
+    fn test_for_css() {
+        assert_eq!(Encoding::for_css(b"a{}", None, None), UTF_8);
+        assert_eq!(Encoding::for_css(b"a{}", None, Some(SHIFT_JIS)), SHIFT_JIS);
+        assert_eq!(
+            Encoding::for_css(b"@charset \"gbk\"; a{}", None, Some(SHIFT_JIS)),
+            GBK
+        );
+        assert_eq!(
+            Encoding::for_css(b"@charset \"gbk\"; a{}", Some(b" Big5 "), None),
+            BIG5
+        );
+        assert_eq!(
+            Encoding::for_css(b"@charset \"gbk\"; a{}", Some(b"bogus"), None),
+            GBK
+        );
+        assert_eq!(
+            Encoding::for_css(b"\xFE\xFF", Some(b"gbk"), Some(SHIFT_JIS)),
+            UTF_16BE
+        );
+        assert_eq!(
+            Encoding::for_css(b"\xEF\xBB\xBF@charset \"gbk\";", None, None),
+            UTF_8
+        );
+        assert_eq!(
+            Encoding::for_css(b"@charset \"utf-16le\";", None, Some(GBK)),
+            UTF_8
+        );
+        assert_eq!(
+            Encoding::for_css(b"@charset \"iso-2022-kr\";", None, Some(GBK)),
+            REPLACEMENT
+        );
+    }
+
+    #[test]
+    fn test_for_css_exact_prefix() {
+        for css in [
+            &b"@charset 'gbk';"[..],
+            b"@charset  \"gbk\";",
+            b"@CHARSET \"gbk\";",
+            b"@charset \"gbk\" ;",
+            b"@charset \"gbk\"",
+            b"@charset \"gb;k\";",
+            b" @charset \"gbk\";",
+            b"@charset \"bogus\";",
+        ] {
+            assert_eq!(Encoding::for_css(css, None, Some(BIG5)), BIG5);
+        }
+        assert_eq!(
+            Encoding::for_css(b"@charset \" gbk \";", None, Some(BIG5)),
+            GBK
+        );
+        let mut css = b"@charset \"".to_vec();
+        css.resize(1020, b' ');
+        css.extend_from_slice(b"gbk\";");
+        assert_eq!(Encoding::for_css(&css, None, Some(BIG5)), BIG5);
+    }
+
+    fn sniff_decode(
+        steps: &[SniffingStep],
+        bytes: &[u8],
+        chunk: usize,
+    ) -> (String, SniffingDecoder) {
+        let mut decoder = SniffingDecoder::new(steps);
+        let mut string = String::new();
+        let mut chunks = bytes.chunks(chunk).peekable();
+        if chunks.peek().is_none() {
+            let (result, _, _) = decoder.decode_to_string(b"", &mut string, true);
+            assert_eq!(result, CoderResult::InputEmpty);
+        }
+        while let Some(mut src) = chunks.next() {
+            let last = chunks.peek().is_none();
+            loop {
+                // Small output buffers exercise `OutputFull` during replay.
+                string.reserve(16);
+                let (result, read, _) = decoder.decode_to_string(src, &mut string, last);
+                src = &src[read..];
+                if result == CoderResult::InputEmpty {
+                    assert!(src.is_empty());
+                    break;
+                }
+            }
+        }
+        (string, decoder)
+    }
+
+    #[test]
+    fn test_sniffing_decoder_html() {
+        let steps = [
+            SniffingStep::Bom,
+            SniffingStep::HtmlPrescan,
+            SniffingStep::Fallback(WINDOWS_1252),
+        ];
+        let html = b"<meta charset=euc-kr><p>\xC7\xD1\xB1\xB9\xBE\xEE</p>";
+        for chunk in [1, 7, 1000] {
+            let (string, decoder) = sniff_decode(&steps, html, chunk);
+            assert_eq!(string, "<meta charset=euc-kr><p>한국어</p>");
+            assert_eq!(decoder.encoding(), Some(EUC_KR));
+            assert_eq!(
+                decoder.decided_by(),
+                Some((SniffingStep::HtmlPrescan, Confidence::Tentative))
+            );
+        }
+        let (string, decoder) =
+            sniff_decode(&steps, b"\xEF\xBB\xBF<meta charset=euc-kr>\xC3\xA9", 5);
+        assert_eq!(string, "<meta charset=euc-kr>é");
+        assert_eq!(
+            decoder.decided_by(),
+            Some((SniffingStep::Bom, Confidence::Certain))
+        );
+        let (string, decoder) = sniff_decode(&steps, b"caf\xE9", 2);
+        assert_eq!(string, "café");
+        assert_eq!(
+            decoder.decided_by(),
+            Some((SniffingStep::Fallback(WINDOWS_1252), Confidence::Tentative))
+        );
+        let (string, decoder) = sniff_decode(&steps, b"", 1);
+        assert_eq!(string, "");
+        assert_eq!(decoder.encoding(), Some(WINDOWS_1252));
+    }
+
+    #[test]
+    fn test_sniffing_decoder_certain() {
+        let steps = [
+            SniffingStep::Transport(SHIFT_JIS),
+            SniffingStep::HtmlPrescan,
+        ];
+        let (string, decoder) = sniff_decode(&steps, b"<meta charset=gbk>\x82\xA0", 3);
+        assert_eq!(string, "<meta charset=gbk>あ");
+        assert_eq!(
+            decoder.decided_by(),
+            Some((SniffingStep::Transport(SHIFT_JIS), Confidence::Certain))
+        );
+        let (string, decoder) =
+            sniff_decode(&[SniffingStep::XmlDeclaration], b"<\x00?\x00x\x00", 4);
+        assert_eq!(string, "<?x");
+        assert_eq!(
+            decoder.decided_by(),
+            Some((SniffingStep::XmlDeclaration, Confidence::Certain))
+        );
+        let (string, _) = sniff_decode(&[SniffingStep::XmlDeclaration], b"\xFE\xFF\x00a", 4);
+        assert_eq!(string, "a");
+        let (string, decoder) = sniff_decode(&[], b"\xC3\xA9", 1);
+        assert_eq!(string, "é");
+        assert_eq!(
+            decoder.decided_by(),
+            Some((SniffingStep::Fallback(UTF_8), Confidence::Tentative))
+        );
+    }
+
+    #[test]
+    fn test_sniffing_decoder_full_buffer() {
+        let mut html = vec![b' '; 1100];
+        html.extend_from_slice(b"<meta charset=gbk>\xE9");
+        let steps = [
+            SniffingStep::HtmlPrescan,
+            SniffingStep::Fallback(WINDOWS_1252),
+        ];
+        let mut decoder = SniffingDecoder::new(&steps);
+        let mut dst = [0u16; 2000];
+        let (result, read, written, replaced) =
+            decoder.decode_to_utf16(&html[..1050], &mut dst, false);
+        assert_eq!(
+            (result, read, written, replaced),
+            (CoderResult::InputEmpty, 1050, 1050, false)
+        );
+        assert_eq!(decoder.encoding(), Some(WINDOWS_1252));
+        let (result, read, written, _) = decoder.decode_to_utf16(&html[1050..], &mut dst, true);
+        assert_eq!(
+            (result, read, written),
+            (CoderResult::InputEmpty, html.len() - 1050, 69)
+        );
+        assert_eq!(dst[68], 0xE9);
+    }
+
+    #[cfg(feature = "detect")]
+    #[test]
+    fn test_sniffing_decoder_detector() {
+        let (bytes, _, _) = WINDOWS_1251.encode("Съешь же ещё этих мягких французских булок");
+        let steps = [
+            SniffingStep::HtmlPrescan,
+            SniffingStep::Detector { allow_utf8: false },
+        ];
+        let (string, decoder) = sniff_decode(&steps, &bytes, 10);
+        assert_eq!(string, "Съешь же ещё этих мягких французских булок");
+        assert_eq!(
+            decoder.decided_by(),
+            Some((
+                SniffingStep::Detector { allow_utf8: false },
+                Confidence::Tentative
+            ))
+        );
+    }
+}
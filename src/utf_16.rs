@@ -198,6 +198,61 @@ impl Utf16Decoder {
     );
 }
 
+/// Checks that `bytes` decode as `encoding` without unpaired surrogates and
+/// without C0 controls other than whitespace. A trailing partial code unit
+/// or surrogate pair is accepted.
+fn is_plausible_utf16(encoding: &'static Encoding, bytes: &[u8]) -> bool {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut buffer = [0u16; 512];
+    let mut src = bytes;
+    loop {
+        let (result, read, written) =
+            decoder.decode_to_utf16_without_replacement(src, &mut buffer, false);
+        if buffer[..written]
+            .iter()
+            .any(|&u| u < 0x20 && !matches!(u, 0x09 | 0x0A | 0x0C | 0x0D))
+        {
+            return false;
+        }
+        src = &src[read..];
+        match result {
+            DecoderResult::InputEmpty => return true,
+            DecoderResult::OutputFull => {}
+            DecoderResult::Malformed(_, _) => return false,
+        }
+    }
+}
+
+pub fn guess_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    let mut units = 0usize;
+    let mut even_zeros = 0usize;
+    let mut odd_zeros = 0usize;
+    for pair in bytes.chunks_exact(2) {
+        units += 1;
+        match (pair[0], pair[1]) {
+            (0, 0) => {}
+            (0, _) => even_zeros += 1,
+            (_, 0) => odd_zeros += 1,
+            _ => {}
+        }
+    }
+    // Characters from U+0001 to U+00FF have their zero byte in the high
+    // half, so it comes second in UTF-16LE and first in UTF-16BE.
+    let (encoding, favored, other) = if odd_zeros > even_zeros {
+        (UTF_16LE, odd_zeros, even_zeros)
+    } else {
+        (UTF_16BE, even_zeros, odd_zeros)
+    };
+    if favored == 0 || favored * 16 < units || other * 8 > favored {
+        return None;
+    }
+    if is_plausible_utf16(encoding, bytes) {
+        Some(encoding)
+    } else {
+        None
+    }
+}
+
 // Any copyright to the test code below this comment is dedicated to the
 // Public Domain. http://creativecommons.org/publicdomain/zero/1.0/
 
@@ -470,4 +525,76 @@ mod tests {
             assert_eq!(output[3], 0x00);
         }
     }
+
+    fn utf16_bytes(string: &str, big_endian: bool) -> Vec<u8> {
+        string
+            .encode_utf16()
+            .flat_map(|u| {
+                if big_endian {
+                    u.to_be_bytes()
+                } else {
+                    u.to_le_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_guess_utf16_without_bom() {
+        for text in [
+            "Hello, world!\r\n",
+            "Grüße aus Köln",
+            "Съешь же ещё этих мягких французских булок",
+            "日本語 (Japanese) 🎌",
+        ] {
+            assert_eq!(
+                Encoding::guess_utf16_without_bom(&utf16_bytes(text, false)),
+                Some(UTF_16LE)
+            );
+            assert_eq!(
+                Encoding::guess_utf16_without_bom(&utf16_bytes(text, true)),
+                Some(UTF_16BE)
+            );
+        }
+        // Truncated in the middle of a surrogate pair
+        let bytes = utf16_bytes("ab🎌", false);
+        assert_eq!(
+            Encoding::guess_utf16_without_bom(&bytes[..bytes.len() - 1]),
+            Some(UTF_16LE)
+        );
+        assert_eq!(
+            Encoding::guess_utf16_without_bom(&bytes[..bytes.len() - 2]),
+            Some(UTF_16LE)
+        );
+    }
+
+    #[test]
+    fn test_guess_utf16_without_bom_rejects() {
+        assert_eq!(Encoding::guess_utf16_without_bom(b""), None);
+        assert_eq!(Encoding::guess_utf16_without_bom(b"Hello, world!"), None);
+        assert_eq!(
+            Encoding::guess_utf16_without_bom("日本語のテキスト".as_bytes()),
+            None
+        );
+        // Mostly CJK without Latin-1 characters isn't confidently UTF-16.
+        assert_eq!(
+            Encoding::guess_utf16_without_bom(&utf16_bytes("日本語のテキストです。", false)),
+            None
+        );
+        // Unpaired surrogate
+        assert_eq!(
+            Encoding::guess_utf16_without_bom(b"a\x00\x00\xDCb\x00"),
+            None
+        );
+        // Zeros at both positions
+        assert_eq!(
+            Encoding::guess_utf16_without_bom(b"\x00a\x00ba\x00b\x00"),
+            None
+        );
+        // Binary-looking controls
+        assert_eq!(
+            Encoding::guess_utf16_without_bom(b"\x01\x00\x02\x00\x03\x00"),
+            None
+        );
+    }
 }
@@ -947,6 +947,10 @@ impl Utf8Encoder {
         Some(byte_length)
     }
 
+    pub fn can_encode(&self, _c: char) -> bool {
+        true
+    }
+
     pub fn encode_from_utf16_raw(
         &mut self,
         src: &[u16],
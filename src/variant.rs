@@ -308,6 +308,20 @@ impl VariantEncoder {
         }
     }
 
+    pub fn can_encode(&self, c: char) -> bool {
+        match *self {
+            VariantEncoder::SingleByte(ref v) => v.can_encode(c),
+            VariantEncoder::Utf8(ref v) => v.can_encode(c),
+            VariantEncoder::Gb18030(ref v) => v.can_encode(c),
+            VariantEncoder::Big5(ref v) => v.can_encode(c),
+            VariantEncoder::EucJp(ref v) => v.can_encode(c),
+            VariantEncoder::Iso2022Jp(ref v) => v.can_encode(c),
+            VariantEncoder::ShiftJis(ref v) => v.can_encode(c),
+            VariantEncoder::EucKr(ref v) => v.can_encode(c),
+            VariantEncoder::UserDefined(ref v) => v.can_encode(c),
+        }
+    }
+
     pub fn encode_from_utf16_raw(
         &mut self,
         src: &[u16],
@@ -168,6 +168,10 @@ impl UserDefinedEncoder {
         Some(byte_length)
     }
 
+    pub fn can_encode(&self, c: char) -> bool {
+        c <= '\u{7F}' || ('\u{F780}'..='\u{F7FF}').contains(&c)
+    }
+
     encoder_functions!(
         eof = {},
         body = {